@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use qutee::{AsPoint, Boundary, ConstCap, QuadTree};
+use qutee::{Area, AsPoint, Boundary, ConstCap, Point, QuadTree};
 use rand::{Rng as _, SeedableRng as _};
 
 const ITEMS: usize = 1_000_000;
@@ -16,10 +16,42 @@ impl AsPoint<usize> for &QuadTreeEntry {
     }
 }
 
+/// A diagonal segment. `CAN_ENCLOSE` controls whether queries may skip `encloses`.
+#[derive(Clone, Copy)]
+struct Segment<const CAN_ENCLOSE: bool> {
+    from: (i64, i64),
+    to: (i64, i64),
+}
+
+impl<const CAN_ENCLOSE: bool> Area<usize> for Segment<CAN_ENCLOSE> {
+    fn contains(&self, point: &Point<usize>) -> bool {
+        let (x, y) = (point.x as i64, point.y as i64);
+        let (dx, dy) = (self.to.0 - self.from.0, self.to.1 - self.from.1);
+        dx * (y - self.from.1) == dy * (x - self.from.0)
+            && (self.from.0.min(self.to.0)..=self.from.0.max(self.to.0)).contains(&x)
+    }
+
+    fn intersects(&self, boundary: &Boundary<usize>) -> bool {
+        let (p1, p2) = (boundary.top_left(), boundary.bottom_right());
+        let (x1, y1, x2, y2) = (p1.x as i64, p1.y as i64, p2.x as i64, p2.y as i64);
+        self.from.0.min(self.to.0) <= x2
+            && self.from.0.max(self.to.0) >= x1
+            && self.from.1.min(self.to.1) <= y2
+            && self.from.1.max(self.to.1) >= y1
+    }
+
+    fn encloses(&self, boundary: &Boundary<usize>) -> bool {
+        self.contains(boundary.top_left()) && self.contains(boundary.bottom_right())
+    }
+
+    fn can_enclose(&self) -> bool {
+        CAN_ENCLOSE
+    }
+}
+
 fn parse_data() -> impl Iterator<Item = QuadTreeEntry> {
     let mut rng = rand::rngs::StdRng::seed_from_u64(10);
     (0..ITEMS)
-        .into_iter()
         .map(move |i| (rng.gen_range(0..32_767), rng.gen_range(0..32_767), i))
         .map(|(x, y, value)| QuadTreeEntry {
             x,
@@ -49,6 +81,45 @@ fn criterion_benchmark(c: &mut Criterion) {
     }
     group.finish();
 
+    let mut group = c.benchmark_group("insert_preallocated");
+    for i in [1_000, 10_000, 100_000, 1_000_000] {
+        group.throughput(criterion::Throughput::Elements(i));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{} Elements", i)),
+            &data[0..i as usize],
+            |b, d| {
+                b.iter(|| {
+                    let mut tree: QuadTree<_, _, ConstCap<16>> = QuadTree::new_with_const_cap(
+                        Boundary::between_points((0, 0), (32_767, 32_767)),
+                    )
+                    .with_preallocated_items(true);
+                    d.iter().for_each(|item| tree.insert(item).unwrap())
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("bulk_load");
+    for i in [1_000, 10_000, 100_000, 1_000_000] {
+        group.throughput(criterion::Throughput::Elements(i));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{} Elements", i)),
+            &data[0..i as usize],
+            |b, d| {
+                b.iter(|| {
+                    QuadTree::<_, _, ConstCap<16>>::bulk_load(
+                        Boundary::between_points((0, 0), (32_767, 32_767)),
+                        ConstCap,
+                        d.iter().map(|item| (item.as_point(), item)),
+                    )
+                    .unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+
     let mut group = c.benchmark_group("insert_unchecked");
     for i in [1_000, 10_000, 100_000, 1_000_000] {
         group.throughput(criterion::Throughput::Elements(i));
@@ -82,7 +153,58 @@ fn criterion_benchmark(c: &mut Criterion) {
         group.bench_with_input(
             BenchmarkId::from_parameter(format!("{query} Area: {area}")),
             &query,
-            |b, q| b.iter(|| tree.query(q.clone()).count()),
+            |b, q| b.iter(|| tree.query(*q).count()),
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("query_segment");
+    group.bench_function("can_enclose", |b| {
+        let segment = Segment::<true> {
+            from: (0, 0),
+            to: (32_767, 32_767),
+        };
+        b.iter(|| tree.query(segment).count())
+    });
+    group.bench_function("can_not_enclose", |b| {
+        let segment = Segment::<false> {
+            from: (0, 0),
+            to: (32_767, 32_767),
+        };
+        b.iter(|| tree.query(segment).count())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("query_for_each");
+    for i in [
+        ((0, 0), (32_767, 32_767)),
+        ((500, 500), (25_000, 25_000)),
+        ((15_000, 15_000), (20_000, 20_000)),
+        ((500, 500), (510, 510)),
+    ] {
+        let area = (i.1 .0 - i.0 .0) * (i.1 .1 - i.0 .1);
+        let query = Boundary::between_points(i.0, i.1);
+        group.bench_with_input(
+            BenchmarkId::new("query", format!("{query} Area: {area}")),
+            &query,
+            |b, q| {
+                b.iter(|| {
+                    let mut count = 0;
+                    tree.query(*q).for_each(|_| count += 1);
+                    count
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("for_each_in_area", format!("{query} Area: {area}")),
+            &query,
+            |b, q| {
+                b.iter(|| {
+                    let mut count = 0;
+                    tree.for_each_in_area(*q, |_, _| count += 1);
+                    count
+                })
+            },
         );
     }
     group.finish();
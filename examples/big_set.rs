@@ -26,7 +26,6 @@ fn main() {
 fn parse_data() -> impl Iterator<Item = QuadTreeEntry> {
     let mut rng = rand::rngs::StdRng::seed_from_u64(10);
     (0..ITEMS)
-        .into_iter()
         .map(move |i| (rng.gen_range(0..32_767), rng.gen_range(0..32_767), i))
         .map(|(x, y, value)| QuadTreeEntry {
             x,
@@ -1,6 +1,6 @@
 use std::fmt::{Debug, Display};
 
-use crate::Point;
+use crate::{IntoPoint, Point, QuadTreeError};
 
 /// Trait defining methods shared by all shapes
 pub trait Area<C: Coordinate>: Clone {
@@ -9,11 +9,34 @@ pub trait Area<C: Coordinate>: Clone {
     /// Checks if this shape and a given boundary intersect at any point
     fn intersects(&self, boundary: &Boundary<C>) -> bool;
     /// Checks if this area fully encloses a given boundary
+    ///
+    /// Queries return all items of an enclosed node without checking them individually,
+    /// so this must only return `true` if every point of the boundary, including its edges, is contained.
     fn encloses(&self, boundary: &Boundary<C>) -> bool;
+    /// Checks if this area can enclose any boundary at all
+    ///
+    /// Areas without an interior, like segments, may return `false` so queries skip calling `encloses` for every node.
+    /// Returning `false` never changes the result of a query, it only disables the shortcut for enclosed nodes.
+    fn can_enclose(&self) -> bool {
+        true
+    }
+}
+
+/// How a node is divided when it is split
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SplitMode {
+    /// Divide both axes into four quadrants
+    #[default]
+    Both,
+    /// Divide only the x axis into a left and a right half
+    HorizontalOnly,
+    /// Divide only the y axis into a top and a bottom half
+    VerticalOnly,
 }
 
 /// A rectangular area
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Boundary<C>
 where
     C: Coordinate,
@@ -32,9 +55,68 @@ where
 }
 
 /// This trait is required for coordinates
+///
+/// Custom types can be used as coordinates if they support arithmetic and conversions from `num_traits`.
+/// Both axes of a point share the same type, so a spatio-temporal index uses the time type for the position as well.
+/// # Example
+/// Index points by position and time using a timestamp newtype
+/// ```
+/// use qutee::*;
+/// use num_traits::{NumCast, ToPrimitive};
+/// use std::ops::{Add, Div, Mul, Rem, Sub};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// struct Timestamp(i64);
+///
+/// macro_rules! impl_op {
+///     ($($op:ident::$f:ident),*) => {$(
+///         impl $op for Timestamp {
+///             type Output = Self;
+///             fn $f(self, rhs: Self) -> Self {
+///                 Timestamp(self.0.$f(rhs.0))
+///             }
+///         }
+///     )*};
+/// }
+/// impl_op!(Add::add, Sub::sub, Mul::mul, Div::div, Rem::rem);
+///
+/// impl ToPrimitive for Timestamp {
+///     fn to_i64(&self) -> Option<i64> {
+///         Some(self.0)
+///     }
+///     fn to_u64(&self) -> Option<u64> {
+///         self.0.to_u64()
+///     }
+/// }
+///
+/// impl NumCast for Timestamp {
+///     fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+///         n.to_i64().map(Timestamp)
+///     }
+/// }
+///
+/// impl Coordinate for Timestamp {}
+///
+/// let t = Timestamp;
+/// // Positions from 0 to 100 over the first 1000 seconds
+/// let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((t(0), t(0)), t(100), t(1000)), 4);
+/// tree.insert_at((t(10), t(5)), "start").unwrap();
+/// tree.insert_at((t(12), t(500)), "middle").unwrap();
+/// tree.insert_at((t(80), t(990)), "end").unwrap();
+/// // Everything which happened between position 0 and 50 in the first 600 seconds
+/// let mut res = tree.query(Boundary::new((t(0), t(0)), t(50), t(600))).collect::<Vec<_>>();
+/// res.sort();
+/// assert_eq!(res, vec![&"middle", &"start"]);
+/// ```
 pub trait Coordinate:
     num_traits::NumOps + Sized + Clone + Copy + num_traits::NumCast + PartialOrd + Debug
 {
+    /// Checks if the value can be used as a coordinate.
+    /// Returns false for non-finite floats.
+    #[inline]
+    fn is_valid(&self) -> bool {
+        true
+    }
 }
 
 impl<C> Boundary<C>
@@ -42,16 +124,31 @@ where
     C: Coordinate,
 {
     /// create a new Boundary from x,y with width and height
-    pub fn new(point: impl Into<Point<C>>, width: C, height: C) -> Self {
-        let p1 = point.into();
+    pub fn new(point: impl IntoPoint<C>, width: C, height: C) -> Self {
+        let p1 = point.into_point();
         let p2 = (p1.x + width, p1.y + height).into();
         Self { p1, p2 }
     }
 
+    /// Create a new Boundary from its top left corner and a `(width, height)` size.
+    /// Negative sizes are normalized so that `top_left` becomes the corresponding opposite corner.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// assert_eq!(Boundary::with_size((1, 2), (10, 20)), Boundary::new((1, 2), 10, 20));
+    /// assert_eq!(Boundary::with_size((10, 10), (-5, -5)), Boundary::between_points((5, 5), (10, 10)));
+    /// ```
+    pub fn with_size(top_left: impl IntoPoint<C>, (width, height): (C, C)) -> Self {
+        let p1 = top_left.into_point();
+        Self::between_points(p1, (p1.x + width, p1.y + height))
+    }
+
     /// Create a new Area between two points
-    pub fn between_points(p1: impl Into<Point<C>>, p2: impl Into<Point<C>>) -> Self {
-        let mut p1: Point<_> = p1.into();
-        let mut p2: Point<_> = p2.into();
+    ///
+    /// Non-finite float coordinates result in an unusable boundary. Use `try_between_points` to check the corners.
+    pub fn between_points(p1: impl IntoPoint<C>, p2: impl IntoPoint<C>) -> Self {
+        let mut p1 = p1.into_point();
+        let mut p2 = p2.into_point();
 
         if p1.x > p2.x {
             std::mem::swap(&mut p1.x, &mut p2.x)
@@ -63,10 +160,67 @@ where
         Self { p1, p2 }
     }
 
-    fn between_points_unchecked(p1: impl Into<Point<C>>, p2: impl Into<Point<C>>) -> Self {
+    /// Create the smallest Boundary containing all points.
+    /// Returns `None` if there are no points.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::try_from_points([Point::new(3, 1), Point::new(1, 5), Point::new(2, 2)]);
+    /// assert_eq!(b, Some(Boundary::between_points((1, 1), (3, 5))));
+    /// assert_eq!(Boundary::<i32>::try_from_points([]), None);
+    /// ```
+    pub fn try_from_points(points: impl IntoIterator<Item = Point<C>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(
+            points.fold(Self::between_points_unchecked(first, first), |b, p| {
+                Self::between_points_unchecked(
+                    (min(b.p1.x, p.x), min(b.p1.y, p.y)),
+                    (max(b.p2.x, p.x), max(b.p2.y, p.y)),
+                )
+            }),
+        )
+    }
+
+    /// Create the smallest Boundary enclosing all given boundaries.
+    /// Returns `None` if there are no boundaries.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::union_all([Boundary::new((0, 0), 1, 1), Boundary::new((5, -2), 1, 1)]);
+    /// assert_eq!(b, Some(Boundary::between_points((0, -2), (6, 1))));
+    /// ```
+    pub fn union_all(boundaries: impl IntoIterator<Item = Boundary<C>>) -> Option<Self> {
+        Self::try_from_points(boundaries.into_iter().flat_map(|b| [b.p1, b.p2]))
+    }
+
+    /// Same as `between_points` except that both corners are checked to be valid coordinates.
+    /// # Errors
+    /// Returns an error if a corner contains a non-finite float.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// assert!(Boundary::try_between_points((0., 0.), (10., 10.)).is_ok());
+    /// assert!(Boundary::try_between_points((0., f64::NAN), (10., 10.)).is_err());
+    /// ```
+    pub fn try_between_points(
+        p1: impl IntoPoint<C>,
+        p2: impl IntoPoint<C>,
+    ) -> Result<Self, QuadTreeError<C>> {
+        let p1 = p1.into_point();
+        let p2 = p2.into_point();
+        for p in [p1, p2] {
+            if !p.is_valid() {
+                return Err(QuadTreeError::InvalidPoint(p));
+            }
+        }
+        Ok(Self::between_points(p1, p2))
+    }
+
+    fn between_points_unchecked(p1: impl IntoPoint<C>, p2: impl IntoPoint<C>) -> Self {
         Self {
-            p1: p1.into(),
-            p2: p2.into(),
+            p1: p1.into_point(),
+            p2: p2.into_point(),
         }
     }
 
@@ -97,27 +251,108 @@ where
 
     /// Returns the height
     pub fn height(&self) -> C {
-        self.top_border() - self.bottom_border()
+        self.bottom_border() - self.top_border()
     }
 
-    pub(crate) fn split(&self) -> [Boundary<C>; 4] {
-        let dx = self.p2.x - self.p1.x;
-        let dy = self.p2.y - self.p1.y;
+    /// Returns the center
+    ///
+    /// Like the split of a node, integer coordinates are rounded towards the top left corner.
+    /// # Panics
+    /// Panics if `2` can not be represented by the coordinate type.
+    /// # Example
+    /// ```
+    /// use qutee::{Boundary, Point};
+    /// assert_eq!(Boundary::new((0,0), 10, 10).center(), Point::new(5,5));
+    /// assert_eq!(Boundary::new((-3,0), 3, 1).center(), Point::new(-2,0));
+    /// ```
+    pub fn center(&self) -> Point<C> {
         let two = C::from(2).expect("Could not convert 2 to required type");
-        let half_dx = dx / two;
-        let half_dy = dy / two;
-        [
-            Boundary::new(self.p1, half_dx, half_dy),
-            Boundary::between_points_unchecked(
-                (self.p1.x + half_dx, self.p1.y),
-                (self.p2.x, self.p1.y + half_dy),
-            ),
-            Boundary::between_points_unchecked(
-                (self.p1.x, self.p1.y + half_dy),
-                (self.p1.x + half_dx, self.p2.y),
-            ),
-            Boundary::between_points_unchecked((self.p1.x + half_dx, self.p1.y + half_dy), self.p2),
-        ]
+        Point::new(
+            self.p1.x + self.width() / two,
+            self.p1.y + self.height() / two,
+        )
+    }
+
+    /// Returns the area
+    /// # Example
+    /// ```
+    /// use qutee::Boundary;
+    /// assert_eq!(Boundary::new((0u32, 0), 10, 20).area(), 200);
+    /// assert_eq!(Boundary::new((-1.5, 0.), 3., 0.5).area(), 1.5);
+    /// ```
+    pub fn area(&self) -> C {
+        self.width() * self.height()
+    }
+
+    /// Split into four quadrants ordered top left, top right, bottom left and bottom right.
+    ///
+    /// Neighbouring quadrants share the line through the middle of the boundary. The middle is
+    /// rounded towards the top left corner, so for integer coordinates with an odd extent the
+    /// remaining unit is always assigned to the right and bottom quadrants. The quadrants cover
+    /// the boundary exactly without gaps.
+    ///
+    /// Returns `None` if the width or height can not be divided any further. This happens for
+    /// a width or height of zero or once the middle can no longer be represented by the coordinate type.
+    pub(crate) fn split(&self) -> Option<[Boundary<C>; 4]> {
+        let mid = Point::new(
+            split_point(self.p1.x, self.p2.x)?,
+            split_point(self.p1.y, self.p2.y)?,
+        );
+        Some([
+            Boundary::between_points_unchecked(self.p1, mid),
+            Boundary::between_points_unchecked((mid.x, self.p1.y), (self.p2.x, mid.y)),
+            Boundary::between_points_unchecked((self.p1.x, mid.y), (mid.x, self.p2.y)),
+            Boundary::between_points_unchecked(mid, self.p2),
+        ])
+    }
+
+    /// Split according to `mode`.
+    ///
+    /// `SplitMode::Both` returns the same four quadrants as `split`. The other modes only divide a single axis
+    /// and return two halves ordered left and right or top and bottom. Returns `None` if the divided axes can
+    /// not be divided any further.
+    pub(crate) fn split_with(&self, mode: SplitMode) -> Option<Vec<Boundary<C>>> {
+        match mode {
+            SplitMode::Both => self.split().map(Vec::from),
+            SplitMode::HorizontalOnly => {
+                let mid = split_point(self.p1.x, self.p2.x)?;
+                Some(vec![
+                    Boundary::between_points_unchecked(self.p1, (mid, self.p2.y)),
+                    Boundary::between_points_unchecked((mid, self.p1.y), self.p2),
+                ])
+            }
+            SplitMode::VerticalOnly => {
+                let mid = split_point(self.p1.y, self.p2.y)?;
+                Some(vec![
+                    Boundary::between_points_unchecked(self.p1, (self.p2.x, mid)),
+                    Boundary::between_points_unchecked((self.p1.x, mid), self.p2),
+                ])
+            }
+        }
+    }
+
+    /// Returns how a node with this boundary is split if the tree uses adaptive splits.
+    ///
+    /// A boundary which is at least twice as wide as it is high is only divided into a left and a right half,
+    /// one which is at least twice as high as it is wide only into a top and a bottom half.
+    /// All other boundaries are divided into four quadrants.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// assert_eq!(Boundary::new((0, 0), 10, 10).split_mode(), SplitMode::Both);
+    /// assert_eq!(Boundary::new((0, 0), 20, 10).split_mode(), SplitMode::HorizontalOnly);
+    /// assert_eq!(Boundary::new((0, 0), 10, 25).split_mode(), SplitMode::VerticalOnly);
+    /// ```
+    pub fn split_mode(&self) -> SplitMode {
+        let width = to_f64(self.p2.x) - to_f64(self.p1.x);
+        let height = to_f64(self.p2.y) - to_f64(self.p1.y);
+        if width > 0. && width >= 2. * height {
+            SplitMode::HorizontalOnly
+        } else if height > 0. && height >= 2. * width {
+            SplitMode::VerticalOnly
+        } else {
+            SplitMode::Both
+        }
     }
 
     /// Get top left corner
@@ -129,13 +364,370 @@ where
     pub fn bottom_right(&self) -> &Point<C> {
         &self.p2
     }
+
+    /// Checks if this boundary contains the point at x, y
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::new((2, 2), 2, 2);
+    /// assert!(b.contains_xy(3, 3));
+    /// assert!(!b.contains_xy(5, 3));
+    /// ```
+    #[inline]
+    pub fn contains_xy(&self, x: C, y: C) -> bool {
+        !(x < self.p1.x || x > self.p2.x || y < self.p1.y || y > self.p2.y)
+    }
+
+    /// Checks if the whole circle around `center` fits inside this boundary.
+    /// A circle touching an edge is still inside.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::new((0, 0), 10, 10);
+    /// assert!(b.contains_circle(&Point::new(5, 5), 5));
+    /// assert!(!b.contains_circle(&Point::new(5, 5), 6));
+    /// ```
+    pub fn contains_circle(&self, center: &Point<C>, radius: C) -> bool {
+        self.contains(center)
+            && center.x - self.p1.x >= radius
+            && self.p2.x - center.x >= radius
+            && center.y - self.p1.y >= radius
+            && self.p2.y - center.y >= radius
+    }
+
+    /// Checks if this boundary is fully enclosed by `outer`, including its edges.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::new((2, 2), 2, 2);
+    /// assert!(b.within(&Boundary::new((0, 0), 10, 10)));
+    /// assert!(!b.within(&Boundary::new((3, 0), 10, 10)));
+    /// assert!(b.within(&OrientedBox::new((3, 3), (4, 4), 0.5)));
+    /// ```
+    pub fn within<A>(&self, outer: &A) -> bool
+    where
+        A: Area<C>,
+    {
+        outer.encloses(self)
+    }
+
+    /// Returns the area shared by both boundaries or `None` if they do not intersect.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let a = Boundary::new((0, 0), 10, 10);
+    /// let b = Boundary::new((5, 5), 10, 10);
+    /// assert_eq!(a.intersection(&b), Some(Boundary::new((5, 5), 5, 5)));
+    /// assert_eq!(a.intersection(&Boundary::new((20, 20), 1, 1)), None);
+    /// ```
+    pub fn intersection(&self, other: &Boundary<C>) -> Option<Boundary<C>> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let p1 = (max(self.p1.x, other.p1.x), max(self.p1.y, other.p1.y));
+        let p2 = (min(self.p2.x, other.p2.x), min(self.p2.y, other.p2.y));
+        Some(Self::between_points_unchecked(p1, p2))
+    }
+
+    /// Returns the size of the area shared by both boundaries or zero if they do not intersect.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let a = Boundary::new((0, 0), 10, 10);
+    /// assert_eq!(a.overlap_area(&Boundary::new((5, 6), 10, 10)), 20);
+    /// assert_eq!(a.overlap_area(&Boundary::new((20, 20), 1, 1)), 0);
+    /// ```
+    pub fn overlap_area(&self, other: &Boundary<C>) -> C {
+        let p1 = Point::new(max(self.p1.x, other.p1.x), max(self.p1.y, other.p1.y));
+        // Clamping to `p1` makes disjoint axes contribute exactly zero instead of a negative extent
+        let p2 = Point::new(
+            max(min(self.p2.x, other.p2.x), p1.x),
+            max(min(self.p2.y, other.p2.y), p1.y),
+        );
+        Boundary { p1, p2 }.area()
+    }
+
+    /// Returns the point inside this boundary closest to `point`.
+    /// Points inside the boundary are returned unchanged.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::new((0, 0), 10, 10);
+    /// assert_eq!(b.clamp_point(&Point::new(15, -4)), Point::new(10, 0));
+    /// assert_eq!(b.clamp_point(&Point::new(3, 4)), Point::new(3, 4));
+    /// ```
+    pub fn clamp_point(&self, point: &Point<C>) -> Point<C> {
+        Point::new(
+            min(max(point.x, self.p1.x), self.p2.x),
+            min(max(point.y, self.p1.y), self.p2.y),
+        )
+    }
+
+    /// Returns the point on the border of this boundary closest to `point`.
+    /// Points inside the boundary are projected onto the nearest edge.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::new((0, 0), 10, 10);
+    /// assert_eq!(b.closest_point_on_boundary(&Point::new(15, 4)), Point::new(10, 4));
+    /// assert_eq!(b.closest_point_on_boundary(&Point::new(3, 4)), Point::new(0, 4));
+    /// ```
+    pub fn closest_point_on_boundary(&self, point: &Point<C>) -> Point<C> {
+        let Point { x, y } = self.clamp_point(point);
+        if x != point.x || y != point.y {
+            return Point::new(x, y);
+        }
+        let candidates = [
+            (x - self.p1.x, Point::new(self.p1.x, y)),
+            (self.p2.x - x, Point::new(self.p2.x, y)),
+            (y - self.p1.y, Point::new(x, self.p1.y)),
+            (self.p2.y - y, Point::new(x, self.p2.y)),
+        ];
+        let mut closest = candidates[0];
+        for candidate in &candidates[1..] {
+            if candidate.0 < closest.0 {
+                closest = *candidate;
+            }
+        }
+        closest.1
+    }
+
+    /// Intersection over union of two boundaries.
+    /// Returns `0` for disjoint boundaries and `1` for identical ones.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let a = Boundary::new((0, 0), 10, 10);
+    /// assert_eq!(a.iou(&a), 1.);
+    /// assert_eq!(a.iou(&Boundary::new((0, 5), 10, 10)), 1. / 3.);
+    /// ```
+    pub fn iou(&self, other: &Boundary<C>) -> f64 {
+        let Some(intersection) = self.intersection(other) else {
+            return 0.;
+        };
+        let intersection = intersection.area_f64();
+        let union = self.area_f64() + other.area_f64() - intersection;
+        if union > 0. {
+            intersection / union
+        } else if self == other {
+            1.
+        } else {
+            0.
+        }
+    }
+
+    pub(crate) fn to_f64(self) -> Boundary<f64> {
+        Boundary {
+            p1: Point::new(to_f64(self.p1.x), to_f64(self.p1.y)),
+            p2: Point::new(to_f64(self.p2.x), to_f64(self.p2.y)),
+        }
+    }
+
+    pub(crate) fn area_f64(&self) -> f64 {
+        (to_f64(self.p2.x) - to_f64(self.p1.x)) * (to_f64(self.p2.y) - to_f64(self.p1.y))
+    }
+
+    /// Divide the boundary into `n` slices of equal size along its longer axis.
+    /// Slices are ordered left to right or top to bottom and neighbouring slices share their border.
+    ///
+    /// If an integer extent is not divisible by `n`, the remaining units are distributed between the slices,
+    /// so their sizes differ by at most one. Returns an empty `Vec` if `n` is zero.
+    /// # Panics
+    /// Panics if `n` can not be represented by the coordinate type.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::new((0, 0), 10, 4);
+    /// assert_eq!(b.split_into(2), vec![Boundary::new((0, 0), 5, 4), Boundary::new((5, 0), 5, 4)]);
+    /// ```
+    pub fn split_into(&self, n: usize) -> Vec<Boundary<C>> {
+        if n == 0 {
+            return Vec::new();
+        }
+        let cast = |i: usize| C::from(i).expect("Could not convert slice count to required type");
+        let count = cast(n);
+        let is_wide = self.p2.x - self.p1.x >= self.p2.y - self.p1.y;
+        let (start, end) = if is_wide {
+            (self.p1.x, self.p2.x)
+        } else {
+            (self.p1.y, self.p2.y)
+        };
+        let extent = end - start;
+        let size = extent / count;
+        // Zero for floats, the units left over by the integer division otherwise.
+        // The share of the remainder is computed in `i128`, multiplying it in `C` may overflow.
+        let remainder = (extent - size * count).to_i128().unwrap_or_default();
+        let border = |i: usize| match i {
+            0 => start,
+            i if i == n => end,
+            i => {
+                let extra = C::from(remainder * i as i128 / n as i128)
+                    .expect("Could not convert remainder to required type");
+                start + size * cast(i) + extra
+            }
+        };
+        (0..n)
+            .map(|i| {
+                let (from, to) = (border(i), border(i + 1));
+                if is_wide {
+                    Self::between_points_unchecked((from, self.p1.y), (to, self.p2.y))
+                } else {
+                    Self::between_points_unchecked((self.p1.x, from), (self.p2.x, to))
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the column and row of the cell containing the point if the boundary is divided into `cols` × `rows` cells.
+    /// Points on a border between two cells belong to the cell to the right or below.
+    /// Points on the right or bottom border of the boundary belong to the last column or row.
+    /// Returns `None` if the point is outside the boundary or there are no cells.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = Boundary::new((0, 0), 10, 10);
+    /// assert_eq!(b.grid_index(&Point::new(5, 2), 2, 2), Some((1, 0)));
+    /// assert_eq!(b.grid_index(&Point::new(10, 10), 2, 2), Some((1, 1)));
+    /// assert_eq!(b.grid_index(&Point::new(11, 10), 2, 2), None);
+    /// ```
+    pub fn grid_index(&self, point: &Point<C>, cols: usize, rows: usize) -> Option<(usize, usize)> {
+        if cols == 0 || rows == 0 || !self.contains(point) {
+            return None;
+        }
+        let axis = |p: C, min: C, max: C, n: usize| {
+            let (p, min, max) = (to_f64(p), to_f64(min), to_f64(max));
+            let extent = max - min;
+            if extent <= 0. {
+                return 0;
+            }
+            (((p - min) / extent * n as f64) as usize).min(n - 1)
+        };
+        Some((
+            axis(point.x, self.p1.x, self.p2.x, cols),
+            axis(point.y, self.p1.y, self.p2.y, rows),
+        ))
+    }
+}
+
+impl<C> Boundary<C>
+where
+    C: Coordinate + num_traits::PrimInt,
+{
+    /// Iterate over every integer point inside the boundary, including its edges, row by row.
+    ///
+    /// This yields `(width + 1) * (height + 1)` points, which can be a huge number for large boundaries.
+    /// The points are created lazily, so use `take` or similar adapters to limit the work if needed.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let cells = Boundary::new((1, 1), 1, 1).integer_cells().collect::<Vec<_>>();
+    /// assert_eq!(cells, vec![Point::new(1, 1), Point::new(2, 1), Point::new(1, 2), Point::new(2, 2)]);
+    /// ```
+    pub fn integer_cells(&self) -> impl Iterator<Item = Point<C>> {
+        let Self { p1, p2 } = *self;
+        let range = move |from: C, to: C| {
+            std::iter::successors(Some(from), move |c| (*c < to).then(|| *c + C::one()))
+        };
+        range(p1.y, p2.y).flat_map(move |y| range(p1.x, p2.x).map(move |x| Point::new(x, y)))
+    }
+}
+
+/// Returns the middle between `p1` and `p2` rounded towards `p1`.
+/// Returns `None` if it is not strictly between both values.
+fn split_point<C: Coordinate>(p1: C, p2: C) -> Option<C> {
+    let two = C::from(2).expect("Could not convert 2 to required type");
+    let mid = p1 + (p2 - p1) / two;
+    (p1 < mid && mid < p2).then_some(mid)
+}
+
+/// Checks if `area` encloses `boundary`.
+/// `encloses` is only called for areas which can enclose a boundary at all.
+pub(crate) fn area_encloses<C, A>(area: &A, boundary: &Boundary<C>) -> bool
+where
+    C: Coordinate,
+    A: Area<C>,
+{
+    area.can_enclose() && area.encloses(boundary)
+}
+
+pub(crate) fn to_f64<C: Coordinate>(c: C) -> f64 {
+    c.to_f64().unwrap_or(f64::NAN)
+}
+
+fn min<C: Coordinate>(a: C, b: C) -> C {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+fn max<C: Coordinate>(a: C, b: C) -> C {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+/// Collects the smallest Boundary containing all points.
+/// # Panics
+/// Panics if the iterator is empty. Use `Boundary::try_from_points` to handle this case.
+impl<C> FromIterator<Point<C>> for Boundary<C>
+where
+    C: Coordinate,
+{
+    fn from_iter<T: IntoIterator<Item = Point<C>>>(iter: T) -> Self {
+        Self::try_from_points(iter).expect("can not create a boundary from zero points")
+    }
+}
+
+/// Creates a boundary between two corners. The corners do not need to be ordered.
+impl<C> From<((C, C), (C, C))> for Boundary<C>
+where
+    C: Coordinate,
+{
+    fn from((p1, p2): ((C, C), (C, C))) -> Self {
+        Self::between_points(p1, p2)
+    }
+}
+
+/// Creates a boundary between two corners. The corners do not need to be ordered.
+impl<C> From<[[C; 2]; 2]> for Boundary<C>
+where
+    C: Coordinate,
+{
+    fn from([p1, p2]: [[C; 2]; 2]) -> Self {
+        Self::between_points(p1, p2)
+    }
+}
+
+/// Returns the top left and bottom right corner
+impl<C> From<Boundary<C>> for ((C, C), (C, C))
+where
+    C: Coordinate,
+{
+    fn from(Boundary { p1, p2 }: Boundary<C>) -> Self {
+        (p1.into(), p2.into())
+    }
+}
+
+/// Returns the top left and bottom right corner
+impl<C> From<Boundary<C>> for [[C; 2]; 2]
+where
+    C: Coordinate,
+{
+    fn from(Boundary { p1, p2 }: Boundary<C>) -> Self {
+        [p1.into(), p2.into()]
+    }
 }
+
 impl<C> Area<C> for Boundary<C>
 where
     C: Coordinate,
 {
     fn contains(&self, point: &Point<C>) -> bool {
-        !(point.x < self.p1.x || point.x > self.p2.x || point.y < self.p1.y || point.y > self.p2.y)
+        self.contains_xy(point.x, point.y)
     }
 
     fn intersects(&self, Boundary { p1, p2 }: &Boundary<C>) -> bool {
@@ -147,6 +739,23 @@ where
     }
 }
 
+impl<C> Area<C> for &Boundary<C>
+where
+    C: Coordinate,
+{
+    fn contains(&self, point: &Point<C>) -> bool {
+        Boundary::contains(self, point)
+    }
+
+    fn intersects(&self, boundary: &Boundary<C>) -> bool {
+        Boundary::intersects(self, boundary)
+    }
+
+    fn encloses(&self, boundary: &Boundary<C>) -> bool {
+        Boundary::encloses(self, boundary)
+    }
+}
+
 impl Coordinate for usize {}
 impl Coordinate for isize {}
 impl Coordinate for u8 {}
@@ -159,12 +768,24 @@ impl Coordinate for i16 {}
 impl Coordinate for i32 {}
 impl Coordinate for i64 {}
 impl Coordinate for i128 {}
-impl Coordinate for f32 {}
-impl Coordinate for f64 {}
+impl Coordinate for f32 {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.is_finite()
+    }
+}
+
+impl Coordinate for f64 {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.is_finite()
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::{Area, Boundary, Point};
+    use crate::{Area, Boundary, Circle, Point, SplitMode};
+    use rand::{Rng, SeedableRng};
     use test_case::test_case;
 
     #[test_case(1,1,2,2 => Boundary::new((1,1),1,1); "Simple case")]
@@ -175,16 +796,322 @@ mod tests {
         Boundary::between_points((x1, y1), (x2, y2))
     }
 
+    #[test_case((1,1), (1,1) => Boundary::new((1,1),1,1); "Simple case")]
+    #[test_case((2,1), (-1,1) => Boundary::new((1,1),1,1); "Negative width")]
+    #[test_case((1,2), (1,-1) => Boundary::new((1,1),1,1); "Negative height")]
+    #[test_case((2,2), (-1,-1) => Boundary::new((1,1),1,1); "Negative both")]
+    fn boundary_with_size(top_left: (isize, isize), size: (isize, isize)) -> Boundary<isize> {
+        Boundary::with_size(top_left, size)
+    }
+
+    #[test_case((4, 3) => Point::new(4, 0); "inside near top")]
+    #[test_case((8, 5) => Point::new(10, 5); "inside near right")]
+    #[test_case((5, 9) => Point::new(5, 10); "inside near bottom")]
+    #[test_case((0, 7) => Point::new(0, 7); "on edge")]
+    #[test_case((-5, 4) => Point::new(0, 4); "outside left face")]
+    #[test_case((6, 14) => Point::new(6, 10); "outside bottom face")]
+    #[test_case((-3, -2) => Point::new(0, 0); "outside top left corner")]
+    #[test_case((12, 15) => Point::new(10, 10); "outside bottom right corner")]
+    fn closest_point_on_boundary(point: (i32, i32)) -> Point<i32> {
+        Boundary::new((0, 0), 10, 10).closest_point_on_boundary(&point.into())
+    }
+
+    #[test_case((5, 5), 2 => true; "inside")]
+    #[test_case((3, 5), 3 => true; "touching left edge")]
+    #[test_case((5, 8), 2 => true; "touching bottom edge")]
+    #[test_case((2, 5), 3 => false; "poking out left")]
+    #[test_case((5, 9), 2 => false; "poking out bottom")]
+    #[test_case((12, 5), 1 => false; "center outside")]
+    fn contains_circle(center: (u32, u32), radius: u32) -> bool {
+        Boundary::new((0, 0), 10, 10).contains_circle(&center.into(), radius)
+    }
+
+    #[test_case((0, 0), (10, 20) => (10, 20); "positive")]
+    #[test_case((10, 20), (0, 0) => (10, 20); "reversed points")]
+    #[test_case((-5, -8), (5, 2) => (10, 10); "negative corner")]
+    #[test_case((3, 3), (3, 7) => (0, 4); "zero width")]
+    fn width_and_height(p1: (i32, i32), p2: (i32, i32)) -> (i32, i32) {
+        let b = Boundary::between_points(p1, p2);
+        (b.width(), b.height())
+    }
+
+    #[test_case((0, 0), 10, 20 => (10, 20); "origin")]
+    #[test_case((5, 7), 3, 0 => (3, 0); "zero height")]
+    #[test_case((0, 0), u32::MAX, u32::MAX => (u32::MAX, u32::MAX); "full range")]
+    fn width_and_height_unsigned(top_left: (u32, u32), width: u32, height: u32) -> (u32, u32) {
+        let b = Boundary::new(top_left, width, height);
+        (b.width(), b.height())
+    }
+
+    #[test]
+    fn center_matches_split() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(758);
+        for _ in 0..100 {
+            let p1 = Point::new(rng.gen_range(-100..100), rng.gen_range(-100..100));
+            let b = Boundary::new(p1, rng.gen_range(2..50), rng.gen_range(2..50));
+            let quadrants = b.split().unwrap();
+            assert_eq!(b.center(), quadrants[0].p2);
+            assert_eq!(b.center(), quadrants[3].p1);
+        }
+        assert_eq!(
+            Boundary::new((0u8, 0), 255, 255).center(),
+            Point::new(127, 127)
+        );
+        assert_eq!(
+            Boundary::new((-1., 1.), 3., 5.).center(),
+            Point::new(0.5, 3.5)
+        );
+    }
+
+    #[test]
+    fn width_and_height_float() {
+        let b = Boundary::new((-1.5, 2.), 4., 0.5);
+        assert_eq!((b.width(), b.height()), (4., 0.5));
+    }
+
+    #[test_case((0, 0), 10, 10 => 100; "identical")]
+    #[test_case((2, 3), 4, 5 => 20; "enclosed")]
+    #[test_case((-5, -5), 30, 30 => 100; "enclosing")]
+    #[test_case((5, 6), 10, 10 => 20; "partial")]
+    #[test_case((10, 0), 5, 10 => 0; "touching edge")]
+    #[test_case((20, 20), 5, 5 => 0; "disjoint")]
+    #[test_case((-20, 2), 5, 5 => 0; "disjoint with negative product")]
+    fn overlap_area(top_left: (i32, i32), width: i32, height: i32) -> i32 {
+        let a = Boundary::new((0, 0), 10, 10);
+        let b = Boundary::new(top_left, width, height);
+        assert_eq!(a.overlap_area(&b), b.overlap_area(&a));
+        a.overlap_area(&b)
+    }
+
+    #[test]
+    fn within_circle() {
+        let b = Boundary::new((4., 4.), 2., 2.);
+        assert!(b.within(&Circle::new((5., 5.), 10.)));
+        assert!(!b.within(&Circle::new((5., 5.), 1.)));
+        // The corners are exactly on the circle
+        let exact = Circle::new((5., 5.), 2f64.sqrt());
+        assert!(b.within(&exact));
+        assert!(!Boundary::new((4., 4.), 2., 3.).within(&exact));
+    }
+
+    #[test]
+    fn tuple_round_trip() {
+        let b = Boundary::from(((5, 1), (2, 7)));
+        assert_eq!(b, Boundary::between_points((2, 1), (5, 7)));
+        let corners: ((i32, i32), (i32, i32)) = b.into();
+        assert_eq!(corners, ((2, 1), (5, 7)));
+        assert_eq!(Boundary::from(corners), b);
+    }
+
+    #[test]
+    fn array_round_trip() {
+        let b = Boundary::from([[5., 1.], [2., 7.]]);
+        assert_eq!(b, Boundary::between_points((2., 1.), (5., 7.)));
+        let corners: [[f64; 2]; 2] = b.into();
+        assert_eq!(corners, [[2., 1.], [5., 7.]]);
+        assert_eq!(Boundary::from(corners), b);
+    }
+
+    #[test_case(f64::NAN, 0.; "NaN x")]
+    #[test_case(0., f64::NAN; "NaN y")]
+    #[test_case(f64::INFINITY, 0.; "infinite x")]
+    #[test_case(0., f64::NEG_INFINITY; "negative infinite y")]
+    fn try_between_points_invalid(x: f64, y: f64) {
+        assert_eq!(
+            Boundary::try_between_points((x, y), (1., 1.)).map_err(|e| format!("{e}")),
+            Err(format!(
+                "point {} is not a valid coordinate",
+                Point::new(x, y)
+            ))
+        );
+        assert!(Boundary::try_between_points((1., 1.), (x, y)).is_err());
+    }
+
+    #[test]
+    fn try_between_points_valid() {
+        assert_eq!(
+            Boundary::try_between_points((2., 2.), (1., 1.)),
+            Ok(Boundary::new((1., 1.), 1., 1.))
+        );
+    }
+
+    #[test]
+    fn union_all() {
+        let boundaries = [
+            Boundary::new((0, 0), 2, 2),
+            Boundary::new((10, -5), 3, 1),
+            Boundary::new((-4, 7), 1, 6),
+        ];
+        let union = Boundary::union_all(boundaries).unwrap();
+        assert_eq!(union, Boundary::between_points((-4, -5), (13, 13)));
+        assert!(boundaries.iter().all(|b| union.encloses(b)));
+        assert_eq!(Boundary::<i32>::union_all([]), None);
+    }
+
+    #[test]
+    fn collect_points() {
+        let points = [(3, -4), (-7, 2), (0, 0), (5, 9), (-1, -1)].map(Point::from);
+        let b: Boundary<i32> = points.iter().copied().collect();
+        assert_eq!(b, Boundary::between_points((-7, -4), (5, 9)));
+        assert!(points.iter().all(|p| b.contains(p)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn collect_no_points() {
+        let _: Boundary<i32> = std::iter::empty().collect();
+    }
+
+    #[test_case(0, 0 => Some((0, 0)); "top left corner")]
+    #[test_case(12, 0 => Some((2, 0)); "top right corner")]
+    #[test_case(0, 12 => Some((0, 1)); "bottom left corner")]
+    #[test_case(12, 12 => Some((2, 1)); "bottom right corner")]
+    #[test_case(6, 6 => Some((1, 1)); "center")]
+    #[test_case(4, 5 => Some((1, 0)); "on column border")]
+    #[test_case(13, 5 => None; "right of boundary")]
+    #[test_case(-1, 5 => None; "left of boundary")]
+    #[test_case(5, 13 => None; "below boundary")]
+    fn grid_index(x: i32, y: i32) -> Option<(usize, usize)> {
+        Boundary::new((0, 0), 12, 12).grid_index(&Point::new(x, y), 3, 2)
+    }
+
+    #[test]
+    fn split_into_strips() {
+        let b = Boundary::new((2, 0), 1022, 10);
+        let strips = b.split_into(4);
+        assert_eq!(strips.len(), 4);
+        assert_eq!(strips[0].left_border(), 2);
+        assert_eq!(strips[3].right_border(), 1024);
+        for w in strips.windows(2) {
+            assert_eq!(w[0].right_border(), w[1].left_border());
+        }
+        let widths = strips.iter().map(Boundary::width).collect::<Vec<_>>();
+        assert_eq!(widths.iter().sum::<i32>(), b.width());
+        assert!(widths.iter().all(|w| *w == 255 || *w == 256), "{widths:?}");
+        for strip in &strips {
+            assert_eq!((strip.top_border(), strip.bottom_border()), (0, 10));
+        }
+        assert_eq!(b.split_into(1), vec![b]);
+        assert!(b.split_into(0).is_empty());
+    }
+
+    #[test]
+    fn split_into_along_longer_axis() {
+        let b = Boundary::new((0u8, 0), 100, 250);
+        let strips = b.split_into(3);
+        assert_eq!(
+            strips,
+            vec![
+                Boundary::new((0, 0), 100, 83),
+                Boundary::new((0, 83), 100, 83),
+                Boundary::new((0, 166), 100, 84),
+            ]
+        );
+        let b = Boundary::new((0., 0.), 1., 3.);
+        assert_eq!(b.split_into(3)[1], Boundary::new((0., 1.), 1., 1.));
+    }
+
+    #[test]
+    fn split_into_small_integer_type() {
+        let b = Boundary::new((0u8, 0), 239, 0);
+        let strips = b.split_into(20);
+        assert_eq!(strips.len(), 20);
+        assert_eq!(strips[0].left_border(), 0);
+        assert_eq!(strips[19].right_border(), 239);
+        for w in strips.windows(2) {
+            assert_eq!(w[0].right_border(), w[1].left_border());
+        }
+        assert!(strips.iter().all(|s| s.width() == 11 || s.width() == 12));
+    }
+
+    #[test]
+    fn integer_cells() {
+        let b = Boundary::new((-2, 3), 4, 2);
+        let cells = b.integer_cells().collect::<Vec<_>>();
+        assert_eq!(cells.len() as i32, (b.width() + 1) * (2 + 1));
+        assert!(cells.iter().all(|p| b.contains(p)));
+        assert_eq!(cells.first(), Some(&Point::new(-2, 3)));
+        assert_eq!(cells.last(), Some(&Point::new(2, 5)));
+        // Edges at the limits of the type do not overflow
+        let b = Boundary::new((250u8, 0), 5, 0);
+        assert_eq!(b.integer_cells().count(), 6);
+        assert_eq!(Boundary::new((0, 0), 0, 0).integer_cells().count(), 1);
+    }
+
+    #[test]
+    fn grid_index_without_cells() {
+        let b = Boundary::new((0, 0), 12, 12);
+        assert_eq!(b.grid_index(&Point::new(1, 1), 0, 2), None);
+        assert_eq!(b.grid_index(&Point::new(1, 1), 2, 0), None);
+    }
+
+    #[test_case(Boundary::new((3, 0), 0, 10); "zero width")]
+    #[test_case(Boundary::new((0, 3), 10, 0); "zero height")]
+    #[test_case(Boundary::new((0, 0), 1, 10); "width of one unit")]
+    fn split_degenerate(b: Boundary<i32>) {
+        assert_eq!(b.split(), None);
+    }
+
+    #[test]
+    fn split_degenerate_float() {
+        assert_eq!(Boundary::new((1., 0.), 0., 10.).split(), None);
+        let tiny = Boundary::between_points((1., 0.), (1. + f64::EPSILON, 10.));
+        assert_eq!(tiny.split(), None);
+        assert!(Boundary::new((1., 0.), 1e-9, 10.).split().is_some());
+    }
+
     #[test]
     fn split_boundary_equal() {
         let b = Boundary::new((0, 0), 10, 10);
-        let split = b.split();
+        let split = b.split().unwrap();
         assert_eq!(split[0], Boundary::new((0, 0), 5, 5));
         assert_eq!(split[1], Boundary::new((5, 0), 5, 5));
         assert_eq!(split[2], Boundary::new((0, 5), 5, 5));
         assert_eq!(split[3], Boundary::new((5, 5), 5, 5));
     }
 
+    #[test]
+    fn split_with_single_axis() {
+        let b = Boundary::new((0, 0), 20, 6);
+        assert_eq!(
+            b.split_with(SplitMode::HorizontalOnly),
+            Some(vec![
+                Boundary::new((0, 0), 10, 6),
+                Boundary::new((10, 0), 10, 6)
+            ])
+        );
+        assert_eq!(
+            b.split_with(SplitMode::VerticalOnly),
+            Some(vec![
+                Boundary::new((0, 0), 20, 3),
+                Boundary::new((0, 3), 20, 3)
+            ])
+        );
+        assert_eq!(b.split_with(SplitMode::Both), b.split().map(Vec::from));
+        // A line can still be halved along its length
+        let line = Boundary::new((0, 3), 10, 0);
+        assert_eq!(line.split_with(SplitMode::Both), None);
+        assert_eq!(line.split_with(SplitMode::VerticalOnly), None);
+        assert_eq!(
+            line.split_with(SplitMode::HorizontalOnly),
+            Some(vec![
+                Boundary::new((0, 3), 5, 0),
+                Boundary::new((5, 3), 5, 0)
+            ])
+        );
+    }
+
+    #[test_case(10, 10 => SplitMode::Both; "square")]
+    #[test_case(19, 10 => SplitMode::Both; "slightly wide")]
+    #[test_case(20, 10 => SplitMode::HorizontalOnly; "wide")]
+    #[test_case(10, 0 => SplitMode::HorizontalOnly; "horizontal line")]
+    #[test_case(10, 20 => SplitMode::VerticalOnly; "high")]
+    #[test_case(0, 10 => SplitMode::VerticalOnly; "vertical line")]
+    #[test_case(0, 0 => SplitMode::Both; "point")]
+    fn split_mode(width: u32, height: u32) -> SplitMode {
+        Boundary::new((0, 0), width, height).split_mode()
+    }
+
     #[test_case(3,3 => true; "Contains point")]
     #[test_case(2,2 => true; "Contains point on border")]
     #[test_case(4,4 => true; "Contains point on border 2")]
@@ -198,6 +1125,18 @@ mod tests {
         b.contains(&p)
     }
 
+    #[test_case(3,3 => true; "Contains point")]
+    #[test_case(2,2 => true; "Contains point on border")]
+    #[test_case(4,4 => true; "Contains point on border 2")]
+    #[test_case(1,3 => false; "Point above")]
+    #[test_case(5,3 => false; "Point below")]
+    #[test_case(3,1 => false; "Point left")]
+    #[test_case(3,5 => false; "Point right")]
+    fn boundary_contains_xy(x: usize, y: usize) -> bool {
+        let b = Boundary::new((2, 2), 2, 2);
+        b.contains_xy(x, y)
+    }
+
     #[test_case(2,2,1,1 => true; "b inside a")]
     #[test_case(0,0,6,6 => true; "a inside b")]
     #[test_case(0,2,3,1 => true; "left overlap")]
@@ -218,6 +1157,16 @@ mod tests {
         a.intersects(&b)
     }
 
+    #[test_case((0., 0.), 10., 10. => 1.; "identical")]
+    #[test_case((5., 0.), 10., 10. => 1. / 3.; "half overlapping")]
+    #[test_case((20., 20.), 5., 5. => 0.; "disjoint")]
+    fn boundary_iou(point: (f64, f64), width: f64, height: f64) -> f64 {
+        let a = Boundary::new((0., 0.), 10., 10.);
+        let b = Boundary::new(point, width, height);
+        assert_eq!(a.iou(&b), b.iou(&a));
+        a.iou(&b)
+    }
+
     #[test]
     fn format_point() {
         let p = Point::new(12, 34);
@@ -230,10 +1179,47 @@ mod tests {
         assert_eq!("(12,34),(23,45)", format!("{b}"))
     }
 
+    #[test]
+    fn split_odd_integer_boundaries_partition_parent() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        for _ in 0..1_000 {
+            let p1 = (rng.gen_range(-1_000..1_000), rng.gen_range(-1_000..1_000));
+            let width = rng.gen_range(1..50) * 2 + 1;
+            let height = rng.gen_range(1..50) * 2 + 1;
+            let b = Boundary::new(p1, width, height);
+            let split = b.split().unwrap();
+
+            assert_eq!(
+                split.iter().map(Boundary::area_f64).sum::<f64>(),
+                b.area_f64()
+            );
+            for (i, x) in split.iter().enumerate() {
+                assert!(b.encloses(x));
+                for y in &split[i + 1..] {
+                    assert_eq!(x.intersection(y).map(|i| i.area_f64()), Some(0.));
+                }
+            }
+            // The remaining unit goes to the right and bottom quadrants
+            assert_eq!(split[1].width(), split[0].width() + 1);
+            assert_eq!(split[3].width(), split[2].width() + 1);
+            assert_eq!(
+                split[2].bottom_border() - split[2].top_border(),
+                split[0].bottom_border() - split[0].top_border() + 1
+            );
+
+            for x in b.left_border()..=b.right_border() {
+                for y in b.top_border()..=b.bottom_border() {
+                    let p = Point::new(x, y);
+                    assert!(split[crate::quadrant_index(&split[0], 4, &p)].contains(&p));
+                }
+            }
+        }
+    }
+
     #[test]
     fn tree_spit_test() {
         let b = Boundary::between_points((16383usize, 16383), (32766, 32766));
-        let sub_bounds = b.split();
+        let sub_bounds = b.split().unwrap();
         assert_eq!(
             sub_bounds[0],
             Boundary {
@@ -287,4 +1273,64 @@ mod tests {
     fn bottom_border(p1: impl Into<Point<i32>>, p2: impl Into<Point<i32>>) -> i32 {
         Boundary::between_points(p1, p2).bottom_border()
     }
+
+    /// Unsigned clock ticks with only the impls `Coordinate` requires
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    struct Ticks(u32);
+
+    macro_rules! forward_ops {
+        ($($op:ident::$f:ident),*) => {$(
+            impl std::ops::$op for Ticks {
+                type Output = Self;
+                fn $f(self, rhs: Self) -> Self {
+                    Ticks(std::ops::$op::$f(self.0, rhs.0))
+                }
+            }
+        )*};
+    }
+    forward_ops!(Add::add, Sub::sub, Mul::mul, Div::div, Rem::rem);
+
+    impl num_traits::ToPrimitive for Ticks {
+        fn to_i64(&self) -> Option<i64> {
+            Some(self.0.into())
+        }
+        fn to_u64(&self) -> Option<u64> {
+            Some(self.0.into())
+        }
+    }
+
+    impl num_traits::NumCast for Ticks {
+        fn from<T: num_traits::ToPrimitive>(n: T) -> Option<Self> {
+            n.to_u32().map(Ticks)
+        }
+    }
+
+    impl crate::Coordinate for Ticks {}
+
+    #[test]
+    fn time_axis() {
+        let t = Ticks;
+        let mut tree =
+            crate::QuadTree::new_with_dyn_cap(Boundary::new((t(0), t(0)), t(100), t(10_000)), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(729);
+        let mut items = Vec::new();
+        for i in 0..500 {
+            let p = Point::new(t(rng.gen_range(0..=100)), t(rng.gen_range(0..=10_000)));
+            assert!(tree.insert_at(p, i).is_ok());
+            items.push((p, i));
+        }
+        assert!(tree.depth() > 0);
+        assert!(tree.insert_at((t(50), t(10_001)), 500).is_err());
+        // Everything in the second half of the area during a time window
+        let window = Boundary::new((t(50), t(2_000)), t(50), t(3_000));
+        let mut expected = items
+            .iter()
+            .filter(|(p, _)| window.contains(p))
+            .map(|(_, i)| *i)
+            .collect::<Vec<_>>();
+        let mut res = tree.query(window).copied().collect::<Vec<_>>();
+        expected.sort();
+        res.sort();
+        assert_eq!(res, expected);
+    }
 }
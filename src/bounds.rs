@@ -1,25 +1,90 @@
+use crate::{Coordinate, Point};
+
 pub trait Capacity: Clone + Copy {
     fn capacity(&self) -> usize;
+
+    /// Increase the capacity of a full node instead of splitting it.
+    /// Returns false if the capacity can not grow any further.
+    #[inline]
+    fn grow(&mut self) -> bool {
+        false
+    }
+
+    /// The capacity used for newly created nodes
+    #[inline]
+    fn for_new_node(&self) -> Self {
+        *self
+    }
+}
+
+/// Decides if a node has room for another item
+pub trait ItemCapacity<Item>: Capacity {
+    #[inline]
+    fn has_room<C: Coordinate>(&self, items: &[(Point<C>, Item)], _item: &Item) -> bool {
+        items.len() < self.capacity()
+    }
+
+    /// Checks if a node has room for all `items`, as if they were added one after another.
+    /// Override this if `has_room` has to look at all items of the node, e.g. to sum them up,
+    /// so the check does not become quadratic.
+    #[inline]
+    fn has_room_for_all<C: Coordinate>(&self, items: &[(Point<C>, Item)]) -> bool {
+        (0..items.len()).all(|i| self.has_room(&items[..i], &items[i].1))
+    }
+}
+
+/// Values which can be turned into a capacity, used by `QuadTree::new`.
+/// A `usize` becomes a [`DynCap`], capacity types are used as they are.
+pub trait IntoCapacity {
+    /// The capacity type of the tree
+    type Capacity: Capacity;
+
+    /// Convert into the capacity
+    fn into_capacity(self) -> Self::Capacity;
+}
+
+impl IntoCapacity for usize {
+    type Capacity = DynCap;
+
+    #[inline]
+    fn into_capacity(self) -> DynCap {
+        DynCap::new(self)
+    }
 }
 
-/// A Capacity known at compile time
+impl<T: Capacity> IntoCapacity for T {
+    type Capacity = T;
+
+    #[inline]
+    fn into_capacity(self) -> T {
+        self
+    }
+}
+
+/// A Capacity known at compile time.
+/// A capacity of 0 is treated as 1.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConstCap<const CAP: usize>;
 impl<const CAP: usize> Capacity for ConstCap<CAP> {
     #[inline]
     fn capacity(&self) -> usize {
-        CAP
+        CAP.max(1)
     }
 }
 
-/// A Capacity known at runtime
+impl<Item, const CAP: usize> ItemCapacity<Item> for ConstCap<CAP> {}
+
+/// A Capacity known at runtime.
+/// A capacity of 0 is treated as 1.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DynCap(pub(super) usize);
 
 impl DynCap {
     /// Create a new DynCap
     pub fn new(cap: usize) -> Self {
-        Self(cap)
+        Self(cap.max(1))
     }
 }
 
@@ -29,3 +94,205 @@ impl Capacity for DynCap {
         self.0
     }
 }
+
+impl<Item> ItemCapacity<Item> for DynCap {}
+
+/// A Capacity which grows instead of splitting a node.
+/// Once a node is full its capacity is doubled until `max` is reached.
+/// Only then the node is split. New nodes start with the initial capacity again.
+/// This avoids the overhead of subdividing trees which only hold a few items.
+/// A capacity of 0 is treated as 1.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GrowCap {
+    initial: usize,
+    current: usize,
+    max: usize,
+}
+
+impl GrowCap {
+    /// Create a new GrowCap starting at `initial` and growing up to `max`.
+    /// If `max` is smaller than `initial` the capacity does not grow.
+    pub fn new(initial: usize, max: usize) -> Self {
+        let initial = initial.max(1);
+        Self {
+            initial,
+            current: initial,
+            max: max.max(initial),
+        }
+    }
+}
+
+impl Capacity for GrowCap {
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.current
+    }
+
+    #[inline]
+    fn grow(&mut self) -> bool {
+        if self.current >= self.max {
+            return false;
+        }
+        self.current = self.current.saturating_mul(2).min(self.max);
+        true
+    }
+
+    #[inline]
+    fn for_new_node(&self) -> Self {
+        Self {
+            current: self.initial,
+            ..*self
+        }
+    }
+}
+
+impl<Item> ItemCapacity<Item> for GrowCap {}
+
+/// The weight of an item used by `WeightedCap`
+pub trait Weight {
+    /// Get the weight of this item
+    fn weight(&self) -> usize;
+}
+
+/// A Capacity limiting the total weight of all items in a node instead of their number.
+/// A node always accepts at least one item, even if it is heavier than the capacity.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightedCap(pub(super) usize);
+
+impl WeightedCap {
+    /// Create a new WeightedCap
+    pub fn new(cap: usize) -> Self {
+        Self(cap)
+    }
+}
+
+impl Capacity for WeightedCap {
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.0
+    }
+}
+
+impl<Item> ItemCapacity<Item> for WeightedCap
+where
+    Item: Weight,
+{
+    fn has_room<C: Coordinate>(&self, items: &[(Point<C>, Item)], item: &Item) -> bool {
+        items.is_empty()
+            || items.iter().map(|(_, i)| i.weight()).sum::<usize>() + item.weight() <= self.0
+    }
+
+    fn has_room_for_all<C: Coordinate>(&self, items: &[(Point<C>, Item)]) -> bool {
+        let mut total = 0;
+        items.iter().enumerate().all(|(i, (_, item))| {
+            total += item.weight();
+            i == 0 || total <= self.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Capacity, ConstCap, DynCap, GrowCap, ItemCapacity, Weight, WeightedCap};
+    use crate::{Boundary, Point, QuadTree};
+
+    #[test]
+    fn zero_capacity_is_one() {
+        assert_eq!(ConstCap::<0>.capacity(), 1);
+        assert_eq!(DynCap::new(0).capacity(), 1);
+
+        let mut tree =
+            QuadTree::<_, _, ConstCap<0>>::new_with_const_cap(Boundary::new((0, 0), 10, 10));
+        assert_eq!(tree.capacity(), 1);
+        for i in 0..10 {
+            assert!(tree.insert_at((i, 10 - i), i).is_ok());
+        }
+        assert_eq!(tree.items.as_ref().unwrap().len(), 1);
+        assert_eq!(tree.iter().count(), 10);
+
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 0);
+        assert_eq!(tree.capacity(), 1);
+        for i in 0..10 {
+            assert!(tree.insert_at((i, 10 - i), i).is_ok());
+        }
+        assert_eq!(tree.items.as_ref().unwrap().len(), 1);
+        assert_eq!(tree.iter().count(), 10);
+    }
+
+    #[test]
+    fn grow_before_split() {
+        let mut tree =
+            QuadTree::new_with_capacity(Boundary::new((0, 0), 100, 100), GrowCap::new(2, 16));
+        for i in 0..16 {
+            assert!(tree.insert_at((i, i), i).is_ok());
+            assert!(tree.quadrants.is_none());
+        }
+        assert_eq!(tree.capacity(), 16);
+        assert!(tree.insert_at((50, 50), 16).is_ok());
+        assert!(tree.quadrants.is_some());
+        let quads = tree.quadrants.as_ref().unwrap();
+        assert!(quads.iter().all(|q| q.capacity() == 2));
+        assert_eq!(tree.iter().count(), 17);
+    }
+
+    #[test]
+    fn grow_cap_steps() {
+        let mut cap = GrowCap::new(3, 10);
+        assert_eq!(cap.capacity(), 3);
+        assert!(cap.grow());
+        assert_eq!(cap.capacity(), 6);
+        assert!(cap.grow());
+        assert_eq!(cap.capacity(), 10);
+        assert!(!cap.grow());
+        assert_eq!(cap.for_new_node().capacity(), 3);
+        assert!(!GrowCap::new(4, 2).grow());
+    }
+
+    impl Weight for usize {
+        fn weight(&self) -> usize {
+            *self
+        }
+    }
+
+    #[test]
+    fn weighted_split() {
+        let mut light = QuadTree::new_with_capacity(Boundary::new((0, 0), 10, 10), WeightedCap(10));
+        for _ in 0..10 {
+            assert!(light.insert_at((1, 1), 1usize).is_ok());
+        }
+        assert!(light.quadrants.is_none());
+
+        let mut heavy = QuadTree::new_with_capacity(Boundary::new((0, 0), 10, 10), WeightedCap(10));
+        assert!(heavy.insert_at((1, 1), 1usize).is_ok());
+        assert!(heavy.insert_at((1, 1), 10usize).is_ok());
+        assert!(heavy.quadrants.is_some());
+        assert_eq!(heavy.items.as_ref().unwrap().len(), 1);
+        assert_eq!(heavy.iter().count(), 2);
+    }
+
+    #[test]
+    fn weighted_accepts_single_heavy_item() {
+        let mut tree = QuadTree::new_with_capacity(Boundary::new((0, 0), 10, 10), WeightedCap(10));
+        assert!(tree.insert_at((1, 1), 100usize).is_ok());
+        assert!(tree.quadrants.is_none());
+    }
+
+    #[test]
+    fn room_for_all_items() {
+        let items = |weights: &[usize]| {
+            weights
+                .iter()
+                .map(|w| (Point::new(0, 0), *w))
+                .collect::<Vec<_>>()
+        };
+        assert!(WeightedCap(10).has_room_for_all(&items(&[])));
+        assert!(WeightedCap(10).has_room_for_all(&items(&[100])));
+        assert!(WeightedCap(10).has_room_for_all(&items(&[3, 3, 4])));
+        assert!(!WeightedCap(10).has_room_for_all(&items(&[3, 3, 5])));
+        assert!(!WeightedCap(10).has_room_for_all(&items(&[20, 1])));
+        assert!(ConstCap::<2>.has_room_for_all(&items(&[100, 100])));
+        assert!(!ConstCap::<2>.has_room_for_all(&items(&[1, 1, 1])));
+    }
+}
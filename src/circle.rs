@@ -0,0 +1,136 @@
+use crate::{boundary::to_f64, Area, Boundary, Coordinate, IntoPoint, Point};
+
+/// A circle around a center point
+///
+/// Distances are computed in `f64`, so differences of unsigned coordinates can not underflow.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Circle<C>
+where
+    C: Coordinate,
+{
+    /// The center of the circle
+    pub center: Point<C>,
+    /// The radius of the circle. Points on the circle are contained.
+    pub radius: C,
+}
+
+impl<C> Circle<C>
+where
+    C: Coordinate,
+{
+    /// Create a new circle
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((5,8), 1);
+    /// tree.insert_at((8,8), 2);
+    /// tree.insert_at((1,1), 3);
+    /// assert_eq!(tree.query(Circle::new((5,5), 3)).collect::<Vec<_>>(), vec![&1]);
+    /// ```
+    pub fn new(center: impl IntoPoint<C>, radius: C) -> Self {
+        Self {
+            center: center.into_point(),
+            radius,
+        }
+    }
+
+    fn contains_f64(&self, (x, y): (f64, f64)) -> bool {
+        let (dx, dy) = (x - to_f64(self.center.x), y - to_f64(self.center.y));
+        let radius = to_f64(self.radius);
+        dx * dx + dy * dy <= radius * radius
+    }
+}
+
+impl<C> Area<C> for Circle<C>
+where
+    C: Coordinate,
+{
+    fn contains(&self, point: &Point<C>) -> bool {
+        self.contains_f64((to_f64(point.x), to_f64(point.y)))
+    }
+
+    /// Checks if the point of the boundary closest to the center is inside the circle
+    fn intersects(&self, boundary: &Boundary<C>) -> bool {
+        let clamp = |c: C, min: C, max: C| to_f64(c).clamp(to_f64(min), to_f64(max));
+        self.contains_f64((
+            clamp(self.center.x, boundary.p1.x, boundary.p2.x),
+            clamp(self.center.y, boundary.p1.y, boundary.p2.y),
+        ))
+    }
+
+    /// Checks if the corner of the boundary farthest from the center is inside the circle
+    fn encloses(&self, boundary: &Boundary<C>) -> bool {
+        let farthest = |c: C, min: C, max: C| {
+            let (c, min, max) = (to_f64(c), to_f64(min), to_f64(max));
+            if c - min > max - c {
+                min
+            } else {
+                max
+            }
+        };
+        self.contains_f64((
+            farthest(self.center.x, boundary.p1.x, boundary.p2.x),
+            farthest(self.center.y, boundary.p1.y, boundary.p2.y),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use test_case::test_case;
+
+    use crate::{Area, Boundary, Circle, QuadTree};
+
+    #[test_case((5, 5) => true; "center")]
+    #[test_case((5, 8) => true; "on the circle")]
+    #[test_case((8, 8) => false; "inside bounding box")]
+    #[test_case((0, 5) => false; "outside")]
+    fn contains(point: (u32, u32)) -> bool {
+        Circle::new((5u32, 5), 3).contains(&point.into())
+    }
+
+    #[test_case(Boundary::between_points((4, 4), (6, 6)) => true; "inside")]
+    #[test_case(Boundary::between_points((0, 0), (20, 20)) => true; "around")]
+    #[test_case(Boundary::between_points((8, 0), (9, 10)) => true; "touching")]
+    #[test_case(Boundary::between_points((8, 8), (9, 9)) => false; "outside near corner")]
+    #[test_case(Boundary::between_points((0, 0), (2, 2)) => false; "outside top left")]
+    fn intersects(boundary: Boundary<u32>) -> bool {
+        Circle::new((5u32, 5), 3).intersects(&boundary)
+    }
+
+    #[test_case(Boundary::between_points((4, 4), (6, 6)) => true; "inside")]
+    #[test_case(Boundary::between_points((3, 3), (7, 7)) => true; "corners on the circle")]
+    #[test_case(Boundary::between_points((3, 3), (8, 7)) => false; "corner outside")]
+    #[test_case(Boundary::between_points((0, 0), (20, 20)) => false; "around")]
+    fn encloses(boundary: Boundary<u32>) -> bool {
+        Circle::new((5u32, 5), 3).encloses(&boundary)
+    }
+
+    #[test]
+    fn query() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0u32, 0), 1000, 1000), 4);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(759);
+        for i in 0..2_000 {
+            let p = (rng.gen_range(0..=1000), rng.gen_range(0..=1000));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        for circle in [
+            Circle::new((500, 500), 200),
+            Circle::new((0, 0), 300),
+            Circle::new((1000, 20), 50),
+        ] {
+            let mut res = tree.query(circle).copied().collect::<Vec<_>>();
+            let mut expected = tree
+                .iter_points()
+                .filter(|(p, _)| circle.contains(p))
+                .map(|(_, i)| *i)
+                .collect::<Vec<_>>();
+            res.sort();
+            expected.sort();
+            assert!(!expected.is_empty());
+            assert_eq!(res, expected);
+        }
+    }
+}
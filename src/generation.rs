@@ -0,0 +1,44 @@
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counts the changes of a tree in debug builds.
+/// Queries remember the generation they started at and panic if it changes while they are alive,
+/// which is only possible if the borrow of the tree was bypassed with unsafe code.
+/// In release builds this is a zero sized type and all checks are removed.
+#[derive(Default)]
+pub(crate) struct Generation(#[cfg(debug_assertions)] AtomicU64);
+
+impl Generation {
+    /// Mark the tree as changed
+    #[inline]
+    pub(crate) fn bump(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            let generation = self.0.get_mut();
+            *generation = generation.wrapping_add(1);
+        }
+    }
+
+    /// The current generation
+    #[cfg(debug_assertions)]
+    #[inline]
+    pub(crate) fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A copy of a tree is a different tree, so it starts with its own count
+impl Clone for Generation {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// The number of changes is not part of the content of a tree
+impl PartialEq for Generation {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Generation {}
@@ -0,0 +1,43 @@
+use geo_types::{coord, Rect};
+
+use crate::Boundary;
+
+/// The minimum of the rectangle becomes the top left corner of the boundary.
+impl From<Rect<f64>> for Boundary<f64> {
+    fn from(rect: Rect<f64>) -> Self {
+        Boundary::between_points(rect.min().x_y(), rect.max().x_y())
+    }
+}
+
+/// The top left corner of the boundary becomes the minimum of the rectangle.
+impl From<Boundary<f64>> for Rect<f64> {
+    fn from(boundary: Boundary<f64>) -> Self {
+        let (p1, p2) = (boundary.top_left(), boundary.bottom_right());
+        Rect::new(coord! { x: p1.x, y: p1.y }, coord! { x: p2.x, y: p2.y })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{coord, Rect};
+
+    use crate::Boundary;
+
+    #[test]
+    fn round_trip() {
+        let boundary = Boundary::between_points((4.5, -2.), (-1., 3.25));
+        let rect = Rect::from(boundary);
+        assert_eq!(rect.min(), coord! { x: -1., y: -2. });
+        assert_eq!(rect.max(), coord! { x: 4.5, y: 3.25 });
+        assert_eq!(Boundary::from(rect), boundary);
+    }
+
+    #[test]
+    fn from_rect() {
+        let rect = Rect::new(coord! { x: 10., y: 0. }, coord! { x: 0., y: 5. });
+        assert_eq!(
+            Boundary::from(rect),
+            Boundary::between_points((0., 0.), (10., 5.))
+        );
+    }
+}
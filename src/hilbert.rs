@@ -0,0 +1,80 @@
+use crate::{boundary::to_f64, Boundary, Coordinate, Point};
+
+/// Number of subdivisions per axis used to compute the index
+const ORDER: u32 = 16;
+
+/// Position of `point` along a Hilbert curve covering `boundary`.
+///
+/// The point is normalized to a grid of 2^16 × 2^16 cells inside the boundary.
+/// Points outside of the boundary are clamped to its border.
+pub(crate) fn index<C>(boundary: &Boundary<C>, point: &Point<C>) -> u64
+where
+    C: Coordinate,
+{
+    let max = (1u32 << ORDER) - 1;
+    let normalize = |c: C, p1: C, p2: C| {
+        let extent = to_f64(p2) - to_f64(p1);
+        if extent > 0. {
+            ((to_f64(c) - to_f64(p1)) / extent * f64::from(max)).clamp(0., f64::from(max)) as u32
+        } else {
+            0
+        }
+    };
+    let mut x = normalize(point.x, boundary.p1.x, boundary.p2.x);
+    let mut y = normalize(point.y, boundary.p1.y, boundary.p2.y);
+    let mut index = 0;
+    let mut s = 1u32 << (ORDER - 1);
+    while s > 0 {
+        let rx = u32::from(x & s > 0);
+        let ry = u32::from(y & s > 0);
+        index += u64::from(s) * u64::from(s) * u64::from((3 * rx) ^ ry);
+        // Rotate the quadrant so the curve of the next level starts at its entry
+        if ry == 0 {
+            if rx == 1 {
+                x = max - x;
+                y = max - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
+
+/// Cells of a 4 × 4 grid in the order of a Hilbert curve of order 2
+#[cfg(test)]
+pub(crate) const ORDER_2_CURVE: [(i32, i32); 16] = [
+    (0, 0),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (1, 3),
+    (1, 2),
+    (2, 2),
+    (2, 3),
+    (3, 3),
+    (3, 2),
+    (3, 1),
+    (2, 1),
+    (2, 0),
+    (3, 0),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::{Boundary, Point};
+
+    #[test]
+    fn follows_curve_on_small_grid() {
+        let boundary = Boundary::new((0, 0), 3, 3);
+        let indices = super::ORDER_2_CURVE
+            .iter()
+            .map(|p| super::index(&boundary, &Point::from(*p)))
+            .collect::<Vec<_>>();
+        assert!(indices.windows(2).all(|w| w[0] < w[1]), "{indices:?}");
+        assert_eq!(indices[0], 0);
+        assert_eq!(indices[15], (1 << 32) - 1);
+    }
+}
@@ -1,6 +1,12 @@
-use crate::{bounds::Capacity, Area, Coordinate, Point, QuadTree};
+use std::iter::FusedIterator;
+
+#[cfg(debug_assertions)]
+use crate::generation::Generation;
+use crate::{boundary::area_encloses, bounds::Capacity, Area, Coordinate, Point, QuadTree};
 
 /// Query Iterator over items and their coordinates
+///
+/// Panics in debug builds if the tree was changed after the query was created, see [`Query`].
 #[derive(Clone)]
 pub struct QueryPoints<'a, PU, A, Item, Cap>(QuerySharedData<'a, PU, A, Item, Cap>)
 where
@@ -17,6 +23,9 @@ where
 {
     area: A,
     stack: Vec<QueryStackItem<'a, C, Item, Cap>>,
+    /// The generation of the tree and its value when the query was created
+    #[cfg(debug_assertions)]
+    generation: (&'a Generation, u64),
 }
 
 impl<'a, C, A, Item, Cap> QuerySharedData<'a, C, A, Item, Cap>
@@ -29,6 +38,8 @@ where
         Self {
             stack: vec![QueryStackItem::new(tree, false, &area)],
             area,
+            #[cfg(debug_assertions)]
+            generation: (&tree.generation, tree.generation.get()),
         }
     }
 }
@@ -67,8 +78,16 @@ where
     }
 }
 
+impl<PU, A, Item, Cap> FusedIterator for QueryPoints<'_, PU, A, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+    A: Area<PU>,
+{
+}
+
 fn query_next<'a, TreeItem, C, A, Cap, RetItem>(
-    QuerySharedData { area, stack }: &mut QuerySharedData<'a, C, A, TreeItem, Cap>,
+    data: &mut QuerySharedData<'a, C, A, TreeItem, Cap>,
 ) -> Option<&'a RetItem>
 where
     RetItem: FromTreeItem<TreeItem, C>,
@@ -76,6 +95,12 @@ where
     A: Area<C>,
     Cap: Capacity,
 {
+    #[cfg(debug_assertions)]
+    {
+        let (generation, start) = data.generation;
+        assert!(generation.get() == start, "tree mutated during query");
+    }
+    let QuerySharedData { area, stack, .. } = data;
     'main: loop {
         let ctx = stack.last_mut()?;
         if let Some(quads) = &mut ctx.quadrants {
@@ -118,9 +143,9 @@ where
         area: &A,
     ) -> Self {
         Self {
-            is_enclosed_by_area: parent_is_enclosed_by_area || area.encloses(&tree.boundary),
+            is_enclosed_by_area: parent_is_enclosed_by_area || area_encloses(area, &tree.boundary),
             items: tree.items.as_deref(),
-            quadrants: tree.quadrants.as_ref().map(|q| q.as_slice()),
+            quadrants: tree.quadrants.as_deref(),
         }
     }
 }
@@ -149,7 +174,7 @@ where
     fn new(tree: &'a QuadTree<C, Item, Cap>) -> Self {
         Self {
             stack: vec![IterStackItem {
-                quadrants: tree.quadrants.as_ref().map(|q| q.as_slice()),
+                quadrants: tree.quadrants.as_deref(),
                 items: tree.items.as_deref(),
             }],
         }
@@ -187,6 +212,13 @@ where
     }
 }
 
+impl<PU, Item, Cap> FusedIterator for IterPoints<'_, PU, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+{
+}
+
 fn iter_next<'a, C, TreeItem, RetItem, Cap>(
     IterSharedData { stack }: &mut IterSharedData<'a, C, TreeItem, Cap>,
 ) -> Option<&'a RetItem>
@@ -211,7 +243,7 @@ where
                 let quad = &quadrants[0];
                 *quadrants = &quadrants[1..];
                 stack.push(IterStackItem {
-                    quadrants: quad.quadrants.as_ref().map(|q| q.as_slice()),
+                    quadrants: quad.quadrants.as_deref(),
                     items: quad.items.as_deref(),
                 });
             } else {
@@ -224,7 +256,97 @@ where
     }
 }
 
+/// Iterator over all items and the path of quadrant indices leading to them.
+/// Created by `QuadTree::iter_with_path`.
+#[derive(Clone)]
+pub struct IterWithPath<'a, PU, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+{
+    shared: IterSharedData<'a, PU, Item, Cap>,
+    path: Vec<u8>,
+    /// Number of quadrants of each node on the stack, nodes may have two or four
+    quadrant_counts: Vec<usize>,
+}
+
+impl<'a, PU, Item, Cap> IterWithPath<'a, PU, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+{
+    pub(super) fn new(tree: &'a QuadTree<PU, Item, Cap>) -> Self {
+        Self {
+            shared: IterSharedData::new(tree),
+            path: Vec::new(),
+            quadrant_counts: vec![tree.quadrants.as_deref().map_or(0, <[_]>::len)],
+        }
+    }
+}
+
+impl<'a, PU, Item, Cap> Iterator for IterWithPath<'a, PU, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+{
+    type Item = (Vec<u8>, &'a Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Self {
+            shared: IterSharedData { stack },
+            path,
+            quadrant_counts,
+        } = self;
+        loop {
+            let ctx = stack.last_mut()?;
+            if let Some(items) = &mut ctx.items {
+                if !items.is_empty() {
+                    let item = &items[0];
+                    *items = &items[1..];
+                    return Some((path.clone(), &item.1));
+                }
+                ctx.items = None;
+            }
+
+            match &mut ctx.quadrants {
+                Some(quadrants) if !quadrants.is_empty() => {
+                    let quad = &quadrants[0];
+                    *quadrants = &quadrants[1..];
+                    let count = quadrant_counts.last().copied().unwrap_or_default();
+                    path.push((count - 1 - quadrants.len()) as u8);
+                    quadrant_counts.push(quad.quadrants.as_deref().map_or(0, <[_]>::len));
+                    stack.push(IterStackItem {
+                        quadrants: quad.quadrants.as_deref(),
+                        items: quad.items.as_deref(),
+                    });
+                }
+                _ => {
+                    stack.pop();
+                    path.pop();
+                    quadrant_counts.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<PU, Item, Cap> FusedIterator for IterWithPath<'_, PU, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+{
+}
+
 /// Query Iterator
+///
+/// Items are yielded quadrant by quadrant, see `QuadTree::query`.
+///
+/// A query borrows the tree, so the tree can not be changed while the query is alive and the query never
+/// yields stale items. This also holds for a tree inside a `RefCell`: the `Ref` has to outlive the query,
+/// so `borrow_mut` fails until the query is dropped.
+/// # Panics
+/// In debug builds `next` panics if the tree was changed after the query was created.
+/// This can only happen if unsafe code bypassed the borrow of the tree.
 #[derive(Clone)]
 #[repr(transparent)]
 pub struct Query<'a, PU, A, Item, Cap>(QuerySharedData<'a, PU, A, Item, Cap>)
@@ -256,6 +378,14 @@ where
     }
 }
 
+impl<PU, A, Item, Cap> FusedIterator for Query<'_, PU, A, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+    A: Area<PU>,
+{
+}
+
 /// Iterator over all items
 #[derive(Clone)]
 #[repr(transparent)]
@@ -286,6 +416,60 @@ where
     }
 }
 
+impl<PU, Item, Cap> FusedIterator for Iter<'_, PU, Item, Cap>
+where
+    Cap: Capacity,
+    PU: Coordinate,
+{
+}
+
+/// Mutable iterator over all items
+///
+/// Only items are exposed, their points can not be changed.
+pub struct IterMut<'a, C, Item, Cap>
+where
+    C: Coordinate,
+{
+    items: std::slice::IterMut<'a, (Point<C>, Item)>,
+    stack: Vec<&'a mut QuadTree<C, Item, Cap>>,
+}
+
+impl<'a, C, Item, Cap> IterMut<'a, C, Item, Cap>
+where
+    C: Coordinate,
+{
+    pub(super) fn new(tree: &'a mut QuadTree<C, Item, Cap>) -> Self {
+        Self {
+            items: Default::default(),
+            stack: vec![tree],
+        }
+    }
+}
+
+impl<'a, C, Item, Cap> Iterator for IterMut<'a, C, Item, Cap>
+where
+    C: Coordinate,
+{
+    type Item = &'a mut Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((_, item)) = self.items.next() {
+                return Some(item);
+            }
+            let QuadTree {
+                items, quadrants, ..
+            } = self.stack.pop()?;
+            self.items = items.as_deref_mut().unwrap_or_default().iter_mut();
+            // Reversed so quadrants are visited in the same order as by `Iter`
+            self.stack
+                .extend(quadrants.iter_mut().flat_map(|q| q.iter_mut()).rev());
+        }
+    }
+}
+
+impl<C, Item, Cap> FusedIterator for IterMut<'_, C, Item, Cap> where C: Coordinate {}
+
 trait FromTreeItem<Item, C>
 where
     C: Coordinate,
@@ -1,7 +1,6 @@
 #![warn(missing_docs)]
 #![warn(clippy::missing_errors_doc)]
 #![warn(clippy::missing_panics_doc)]
-#![warn(clippy::missing_errors_doc)]
 #![warn(unused_unsafe)]
 #![warn(clippy::suspicious)]
 #![warn(clippy::perf)]
@@ -27,42 +26,98 @@
 
 mod boundary;
 mod bounds;
+mod circle;
+mod generation;
+#[cfg(feature = "geo")]
+mod geo;
+mod hilbert;
 mod iter;
+mod metric;
+mod morton;
+mod nearest;
+mod oriented_box;
+mod persist;
+#[cfg(feature = "profiling")]
+mod profile;
+#[cfg(feature = "serde")]
+mod serialize;
+mod shared;
+mod validate;
 
 use std::{
+    collections::HashSet,
     error::Error,
     fmt::{Debug, Display},
+    hash::{BuildHasher, Hash},
 };
 
 pub use boundary::*;
-use bounds::Capacity;
-pub use bounds::{ConstCap, DynCap};
+use bounds::ItemCapacity;
+pub use bounds::{ConstCap, DynCap, GrowCap, IntoCapacity, Weight, WeightedCap};
+pub use circle::Circle;
+use generation::Generation;
 pub use iter::*;
+pub use metric::{Chebyshev, Euclidean, Manhattan, Metric};
+pub use oriented_box::OrientedBox;
+#[cfg(feature = "profiling")]
+pub use profile::QueryProfile;
+pub use shared::SharedQuadTree;
+pub use validate::ValidationError;
 
 ///
 /// # Parameter
 /// C: The type used for coordinates
 /// Item: The type to be saved
 /// CAP: The maximum capacity of each level
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Clone)]
 pub struct QuadTree<C, Item, Cap = DynCap>
 where
     C: Coordinate,
 {
     boundary: Boundary<C>,
-    quadrants: Option<Box<[QuadTree<C, Item, Cap>; 4]>>,
+    /// Either four quadrants or, for nodes split along a single axis, two halves
+    quadrants: Option<Box<[QuadTree<C, Item, Cap>]>>,
     items: Option<Vec<(Point<C>, Item)>>,
     capacity: Cap,
+    /// Allocate the items of new quadrants when a node is split
+    preallocate_items: bool,
+    /// Choose the split mode of new nodes by the aspect ratio of their boundary
+    adaptive_split: bool,
+    /// Number of levels which may still be created below this node
+    max_depth: usize,
+    /// Changes of the tree, checked by queries in debug builds
+    generation: Generation,
 }
 
 /// Possible errors
 #[derive(PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum QuadTreeError<C>
 where
     C: Coordinate,
 {
     /// Point is out of bounds
     OutOfBounds(Boundary<C>, Point<C>),
+    /// Point contains a coordinate which is not valid (e.g. NaN)
+    InvalidPoint(Point<C>),
+}
+
+/// Describes how the tree changed during `QuadTree::insert_at_tracked`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct InsertOutcome {
+    /// A node was subdivided to make room for the item
+    pub split_created: bool,
+    /// Depth of the node the item was stored in. The root has a depth of 0.
+    pub final_depth: usize,
+}
+
+/// Identifies items which describe the same record.
+/// Used by `qutee::QuadTree::insert_keyed` to replace existing items.
+pub trait Keyed {
+    /// The type of the key
+    type Key: Eq;
+    /// Get the key of an item
+    fn key(&self) -> Self::Key;
 }
 
 /// This traits allows a type to be used with `qutee::QuadTree::insert`
@@ -74,8 +129,31 @@ where
     fn as_point(&self) -> Point<C>;
 }
 
+/// Conversion into a `Point`.
+/// This allows custom point types to be used wherever qutee expects a point.
+/// It is implemented for every type implementing `Into<Point<C>>`.
+pub trait IntoPoint<C>
+where
+    C: Coordinate,
+{
+    /// Convert into a `Point`
+    fn into_point(self) -> Point<C>;
+}
+
+/// Conversion from a `Point`.
+/// This allows qutee to return custom point types.
+/// It is implemented for every type implementing `From<Point<C>>`.
+pub trait FromPoint<C>
+where
+    C: Coordinate,
+{
+    /// Convert from a `Point`
+    fn from_point(point: Point<C>) -> Self;
+}
+
 /// A point in two dimensional space
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point<C>
 where
     C: Coordinate,
@@ -103,13 +181,89 @@ where
     pub fn new(x: T, y: T) -> Self {
         Self { x, y }
     }
+
+    /// Checks if both coordinates are valid
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// assert!(Point::new(1., 2.).is_valid());
+    /// assert!(!Point::new(f32::NAN, 2.).is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        self.x.is_valid() && self.y.is_valid()
+    }
+
+    /// Linearly interpolate between this point and `other`.
+    /// `t = 0` returns this point and `t = 1` returns `other`.
+    /// The calculation is done in `f64`. For integer coordinates the result is truncated towards zero.
+    /// # Panics
+    /// Panics if the result can not be represented by the coordinate type.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// assert_eq!(Point::new(0., 0.).lerp(&Point::new(3., 5.), 0.5), Point::new(1.5, 2.5));
+    /// assert_eq!(Point::new(0, 0).lerp(&Point::new(3, 5), 0.5), Point::new(1, 2));
+    /// ```
+    pub fn lerp(&self, other: &Point<T>, t: f64) -> Point<T> {
+        let lerp = |a: T, b: T| {
+            let (a, b) = (boundary::to_f64(a), boundary::to_f64(b));
+            T::from(a + (b - a) * t).expect("interpolated coordinate is out of range")
+        };
+        Point::new(lerp(self.x, other.x), lerp(self.y, other.y))
+    }
+
+    /// Rotate this point around `pivot` by `angle_rad` radians.
+    /// Positive angles rotate the x axis towards the y axis, like [`OrientedBox`].
+    /// The calculation is done in `f64`. For integer coordinates the result is rounded to the nearest integer,
+    /// so repeated rotations accumulate rounding errors.
+    /// # Panics
+    /// Panics if the result can not be represented by the coordinate type.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// use std::f64::consts::FRAC_PI_2;
+    /// assert_eq!(Point::new(3, 1).rotate_around(&Point::new(0, 0), FRAC_PI_2), Point::new(-1, 3));
+    /// ```
+    pub fn rotate_around(&self, pivot: &Point<T>, angle_rad: f64) -> Point<T> {
+        let (sin, cos) = angle_rad.sin_cos();
+        let (px, py) = (boundary::to_f64(pivot.x), boundary::to_f64(pivot.y));
+        let (dx, dy) = (boundary::to_f64(self.x) - px, boundary::to_f64(self.y) - py);
+        // Integer types truncate when converting from `f64`
+        let is_integer = T::from(0.5).and_then(|half| half.to_f64()) != Some(0.5);
+        let convert = |c: f64| {
+            let c = if is_integer { c.round() } else { c };
+            T::from(c).expect("rotated coordinate is out of range")
+        };
+        Point::new(
+            convert(px + dx * cos - dy * sin),
+            convert(py + dx * sin + dy * cos),
+        )
+    }
 }
 
 impl<C, Item, Cap> QuadTree<C, Item, Cap>
 where
-    Cap: Capacity,
+    Cap: ItemCapacity<Item>,
     C: Coordinate,
 {
+    /// Create a new quad tree for a given area where each level of the tree has a given capacity.
+    /// The capacity type is picked from the given capacity, a `usize` becomes a [`DynCap`].
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::new(Boundary::between_points((0,0), (10,10)), 4);
+    /// tree.insert_at((3,5), 1);
+    /// assert_eq!(tree.capacity(), 4);
+    /// let tree = QuadTree::<_, u8, _>::new(Boundary::between_points((0,0), (10,10)), ConstCap::<8>);
+    /// assert_eq!(tree.capacity(), 8);
+    /// ```
+    pub fn new<I>(boundary: Boundary<C>, capacity: I) -> Self
+    where
+        I: IntoCapacity<Capacity = Cap>,
+    {
+        Self::new_with_capacity(boundary, capacity.into_capacity())
+    }
+
     /// Create a new quad tree for a given area where each level of the tree has a given capacity.
     /// # Example
     /// ```
@@ -124,7 +278,88 @@ where
             quadrants: None,
             items: None,
             capacity,
+            preallocate_items: false,
+            adaptive_split: false,
+            max_depth: usize::MAX,
+            generation: Generation::default(),
+        }
+    }
+
+    /// Allocate room for `capacity` items in every new quadrant when a node is split.
+    /// This avoids reallocations for dense inserts at the cost of memory for quadrants which stay empty.
+    /// Disabled by default.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0), (10,10)), 16)
+    ///     .with_preallocated_items(true);
+    /// tree.insert_at((3,5), 1);
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn with_preallocated_items(mut self, preallocate: bool) -> Self {
+        self.preallocate_items = preallocate;
+        self
+    }
+
+    /// Split elongated nodes along a single axis instead of into four quadrants.
+    /// Each node picks its split mode from the aspect ratio of its boundary, see [`Boundary::split_mode`].
+    /// This keeps nodes of an elongated tree roughly square instead of repeating the aspect ratio of the root.
+    /// Disabled by default.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0), (100,10)), 1)
+    ///     .with_adaptive_split(true);
+    /// tree.insert_at((10,5), 1);
+    /// tree.insert_at((90,5), 2);
+    /// assert_eq!(tree.query(Boundary::between_points((50,0), (100,10))).collect::<Vec<_>>(), vec![&2]);
+    /// ```
+    pub fn with_adaptive_split(mut self, adaptive: bool) -> Self {
+        self.adaptive_split = adaptive;
+        self
+    }
+
+    /// Limit the number of levels below the root node.
+    /// Nodes at the maximum depth are not split, they keep all further items even beyond their capacity.
+    /// This bounds the depth of trees holding many items at the same point, which can otherwise only be separated
+    /// once a node is too small to be split. Unlimited by default. Set the limit before inserting items.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0.,0.), (10.,10.)))
+    ///     .with_max_depth(2);
+    /// for i in 0..10 {
+    ///     tree.insert_at((5.,5.), i);
+    /// }
+    /// assert_eq!(tree.depth(), 2);
+    /// assert_eq!(tree.len(), 10);
+    /// ```
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Create an empty node sharing the settings of this node
+    fn new_child(&self, boundary: Boundary<C>, capacity: Cap) -> Self {
+        QuadTree {
+            preallocate_items: self.preallocate_items,
+            adaptive_split: self.adaptive_split,
+            max_depth: self.max_depth.saturating_sub(1),
+            ..Self::new_with_capacity(boundary, capacity)
+        }
+    }
+
+    /// Boundaries of the quadrants this node is divided into when it is split
+    pub(crate) fn split_boundaries(&self) -> Option<Vec<Boundary<C>>> {
+        if self.max_depth == 0 {
+            return None;
         }
+        let mode = if self.adaptive_split {
+            self.boundary.split_mode()
+        } else {
+            SplitMode::Both
+        };
+        self.boundary.split_with(mode)
     }
 
     /// Insert new item into the quad tree.
@@ -139,10 +374,11 @@ where
     /// ```
     pub fn insert_at(
         &mut self,
-        point: impl Into<Point<C>>,
+        point: impl IntoPoint<C>,
         value: Item,
     ) -> Result<(), QuadTreeError<C>> {
-        let point = point.into();
+        self.generation.bump();
+        let point = point.into_point();
         if !self.boundary.contains(&point) {
             return Err(QuadTreeError::OutOfBounds(self.boundary, point));
         }
@@ -158,37 +394,513 @@ where
     /// tree.insert_at_unchecked((5,5), ());
     /// assert_eq!(tree.iter().count(), 1);
     /// ```
-    pub fn insert_at_unchecked(&mut self, point: impl Into<Point<C>>, value: Item) {
+    pub fn insert_at_unchecked(&mut self, point: impl IntoPoint<C>, value: Item) {
+        self.generation.bump();
+        self.insert_tracked_unchecked(point.into_point(), value);
+    }
+
+    /// Same as `insert_at` but returns an id describing the node the item was stored in.
+    ///
+    /// The quadrant indices leading to the node are packed into the high bits of the id,
+    /// 2 bits per level starting at the most significant bits. The lowest 6 bits hold the depth.
+    /// Only the first 29 levels are encoded, so ids of deeper nodes may collide.
+    /// Items stored in the same node get the same id and nodes in the same subtree share a prefix.
+    /// Ids may change once the tree is modified.
+    /// # Errors
+    /// Returns an error if the point is out of bounds.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// assert_eq!(tree.insert_at_with_id((5,5), ()).unwrap(), 0);
+    /// // Stored in the bottom right quadrant (index 3) at depth 1
+    /// assert_eq!(tree.insert_at_with_id((8,8), ()).unwrap(), 0b11 << 62 | 1);
+    /// ```
+    pub fn insert_at_with_id(
+        &mut self,
+        point: impl IntoPoint<C>,
+        value: Item,
+    ) -> Result<u64, QuadTreeError<C>> {
+        self.generation.bump();
+        let point = point.into_point();
+        if !self.boundary.contains(&point) {
+            return Err(QuadTreeError::OutOfBounds(self.boundary, point));
+        }
+        let (outcome, path) = self.insert_unchecked_with_path(point, value);
+        Ok(path | outcome.final_depth.min(MAX_ID_DEPTH_BITS) as u64)
+    }
+
+    /// Same as `insert_at` but reports how the tree changed.
+    /// # Errors
+    /// Returns an error if the point is out of bounds.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// let outcome = tree.insert_at_tracked((5,5), ()).unwrap();
+    /// assert_eq!(outcome, InsertOutcome { split_created: false, final_depth: 0 });
+    /// let outcome = tree.insert_at_tracked((1,1), ()).unwrap();
+    /// assert_eq!(outcome, InsertOutcome { split_created: true, final_depth: 1 });
+    /// ```
+    pub fn insert_at_tracked(
+        &mut self,
+        point: impl IntoPoint<C>,
+        value: Item,
+    ) -> Result<InsertOutcome, QuadTreeError<C>> {
+        self.generation.bump();
+        let point = point.into_point();
+        if !self.boundary.contains(&point) {
+            return Err(QuadTreeError::OutOfBounds(self.boundary, point));
+        }
+        Ok(self.insert_tracked_unchecked(point, value))
+    }
+
+    /// Insert a new item or merge it into an item already stored at exactly the same point.
+    /// If such an item exists, `reduce` is called with the stored item and the new value instead of storing a duplicate.
+    /// # Errors
+    /// Returns an error if the point is out of bounds.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at_reduce((5,5), 2, |sum, v| *sum += v).unwrap();
+    /// tree.insert_at_reduce((5,5), 3, |sum, v| *sum += v).unwrap();
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&5]);
+    /// ```
+    pub fn insert_at_reduce(
+        &mut self,
+        point: impl IntoPoint<C>,
+        value: Item,
+        reduce: impl FnOnce(&mut Item, Item),
+    ) -> Result<(), QuadTreeError<C>> {
+        self.generation.bump();
+        let point = point.into_point();
+        if !self.boundary.contains(&point) {
+            return Err(QuadTreeError::OutOfBounds(self.boundary, point));
+        }
+        match self.find_at_mut(&point) {
+            Some(existing) => reduce(existing, value),
+            None => self.insert_at_unchecked(point, value),
+        }
+        Ok(())
+    }
+
+    /// Returns the first item stored at exactly `point`
+    fn find_at_mut(&mut self, point: &Point<C>) -> Option<&mut Item> {
+        if let Some(index) = self.items.iter().flatten().position(|(p, _)| p == point) {
+            return self.items.as_mut().map(|items| &mut items[index].1);
+        }
+        self.quadrants
+            .iter_mut()
+            .flat_map(|q| q.iter_mut())
+            .filter(|q| q.boundary.contains(point))
+            .find_map(|q| q.find_at_mut(point))
+    }
+
+    /// Same as `insert_at_unchecked` except that the quadrant of the root node can be provided by the caller.
+    ///
+    /// If the root node is full, the item is passed to the quadrant with index `quadrant_hint`
+    /// without comparing the point against the quadrant boundaries.
+    /// Quadrants are ordered top left, top right, bottom left, bottom right.
+    /// Nodes split along a single axis only have the quadrants 0 and 1, see [`QuadTree::with_adaptive_split`].
+    ///
+    /// If `quadrant_hint` is `Some`, it must be the index of the quadrant containing `point`.
+    /// Otherwise the tree is left in an inconsistent state where queries may miss items.
+    /// This is only checked in debug builds.
+    /// # Panics
+    /// Panics if `quadrant_hint` is not a valid quadrant index and the root node is full.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at_unchecked((5,5), 1);
+    /// // (8,8) is inside the bottom right quadrant
+    /// tree.insert_presorted_unchecked((8,8), 2, Some(3));
+    /// assert_eq!(tree.query(Boundary::between_points((6,6), (10,10))).collect::<Vec<_>>(), vec![&2]);
+    /// ```
+    pub fn insert_presorted_unchecked(
+        &mut self,
+        point: impl IntoPoint<C>,
+        value: Item,
+        quadrant_hint: Option<usize>,
+    ) {
+        self.generation.bump();
+        self.insert_unchecked_with_hint(point.into_point(), value, quadrant_hint);
+    }
+
+    #[inline]
+    fn insert_tracked_unchecked(&mut self, point: Point<C>, value: Item) -> InsertOutcome {
+        self.insert_unchecked_with_path(point, value).0
+    }
+
+    /// Inserts an item and returns the quadrant indices leading to its node packed as described in `insert_at_with_id`.
+    #[inline]
+    fn insert_unchecked_with_path(&mut self, point: Point<C>, value: Item) -> (InsertOutcome, u64) {
+        self.insert_unchecked_with_hint(point, value, None)
+    }
+
+    /// Same as `insert_unchecked_with_path`, but uses `quadrant_hint` instead of comparing the point the first time a quadrant is selected.
+    fn insert_unchecked_with_hint(
+        &mut self,
+        point: Point<C>,
+        value: Item,
+        mut quadrant_hint: Option<usize>,
+    ) -> (InsertOutcome, u64) {
         let mut sub_tree = self;
-        let point = point.into();
+        let mut outcome = InsertOutcome::default();
+        let mut path = 0;
         loop {
-            if sub_tree.items.as_ref().map(|i| i.len()).unwrap_or_default()
-                < sub_tree.capacity.capacity()
+            if sub_tree
+                .capacity
+                .has_room(sub_tree.items.as_deref().unwrap_or_default(), &value)
             {
                 sub_tree
                     .items
                     .get_or_insert_with(|| Vec::with_capacity(sub_tree.capacity.capacity()))
                     .push((point, value));
-                return;
+                return (outcome, path);
             }
-            let quads = sub_tree.quadrants.get_or_insert_with(|| {
-                let [b0, b1, b2, b3] = sub_tree.boundary.split();
-                Box::new([
-                    QuadTree::new_with_capacity(b0, sub_tree.capacity),
-                    QuadTree::new_with_capacity(b1, sub_tree.capacity),
-                    QuadTree::new_with_capacity(b2, sub_tree.capacity),
-                    QuadTree::new_with_capacity(b3, sub_tree.capacity),
-                ])
-            });
+            if sub_tree.quadrants.is_none() {
+                if sub_tree.capacity.grow() {
+                    continue;
+                }
+                let Some(boundaries) = sub_tree.split_boundaries() else {
+                    // The node is too small or too deep to be subdivided, so it holds more items than its capacity
+                    sub_tree
+                        .items
+                        .get_or_insert_with(Vec::new)
+                        .push((point, value));
+                    return (outcome, path);
+                };
+                outcome.split_created = true;
+                let capacity = sub_tree.capacity.for_new_node();
+                let quads = boundaries
+                    .into_iter()
+                    .map(|b| {
+                        let mut quad = sub_tree.new_child(b, capacity);
+                        if quad.preallocate_items {
+                            quad.items = Some(Vec::with_capacity(capacity.capacity()));
+                        }
+                        quad
+                    })
+                    .collect();
+                sub_tree.quadrants = Some(quads);
+            }
+            let Some(quads) = &mut sub_tree.quadrants else {
+                unreachable!("quadrants are created above");
+            };
 
-            let is_in_right_half = (quads[0].boundary.p2.x < point.x) as usize;
-            let is_in_bottom_half = (quads[0].boundary.p2.y < point.y) as usize;
-            let index = is_in_bottom_half << 1 | is_in_right_half;
+            let hint = quadrant_hint.take();
+            let index =
+                hint.unwrap_or_else(|| quadrant_index(&quads[0].boundary, quads.len(), &point));
+            // Unchecked inserts may pass points outside of the tree, only hints are verified
+            debug_assert!(hint.is_none() || quads[index].boundary.contains(&point));
             sub_tree = &mut quads[index];
+            if outcome.final_depth < MAX_ID_LEVELS {
+                path |= (index as u64) << (62 - 2 * outcome.final_depth);
+            }
+            outcome.final_depth += 1;
+        }
+    }
+
+    /// Build a tree from items which are already sorted in Morton order (Z-order) relative to `boundary`.
+    /// Quadrants are ordered top left, top right, bottom left, bottom right.
+    /// Items are only stored in leaves.
+    ///
+    /// All points must be inside `boundary` and sorted. This is only checked in debug builds.
+    /// If the precondition is violated, the resulting tree is corrupt and queries may miss items.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let tree = QuadTree::<_,_,ConstCap<1>>::from_sorted_unchecked(
+    ///     Boundary::between_points((0,0), (10,10)),
+    ///     ConstCap,
+    ///     vec![((1,1).into(), 1), ((8,2).into(), 2), ((2,8).into(), 3), ((8,8).into(), 4)],
+    /// );
+    /// assert_eq!(tree.query(Boundary::between_points((0,0), (5,5))).collect::<Vec<_>>(), vec![&1]);
+    /// ```
+    pub fn from_sorted_unchecked(
+        boundary: Boundary<C>,
+        capacity: Cap,
+        points_in_morton_order: Vec<(Point<C>, Item)>,
+    ) -> Self {
+        debug_assert!(
+            points_in_morton_order
+                .iter()
+                .all(|(p, _)| boundary.contains(p)),
+            "all points must be inside the boundary"
+        );
+        let mut tree = Self::new_with_capacity(boundary, capacity);
+        tree.fill_sorted(points_in_morton_order);
+        tree
+    }
+
+    /// Build a tree by inserting all points in the order they are given.
+    /// All points are checked before the first item is inserted.
+    /// # Errors
+    /// Returns an error for the first point that is out of bounds. In this case no tree is built.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let boundary = Boundary::between_points((0,0), (10,10));
+    /// let tree = QuadTree::<_,_,ConstCap<2>>::from_points(boundary, ConstCap, vec![((1,1).into(), 1), ((5,5).into(), 2)]).unwrap();
+    /// assert_eq!(tree.len(), 2);
+    /// assert!(QuadTree::<_,_,ConstCap<2>>::from_points(boundary, ConstCap, vec![((11,11).into(), 1)]).is_err());
+    /// ```
+    pub fn from_points(
+        boundary: Boundary<C>,
+        capacity: Cap,
+        points: impl IntoIterator<Item = (Point<C>, Item)>,
+    ) -> Result<Self, QuadTreeError<C>> {
+        let points = points.into_iter().collect::<Vec<_>>();
+        if let Some((point, _)) = points.iter().find(|(p, _)| !boundary.contains(p)) {
+            return Err(QuadTreeError::OutOfBounds(boundary, *point));
+        }
+        let mut tree = Self::new_with_capacity(boundary, capacity);
+        for (point, item) in points {
+            tree.insert_at_unchecked(point, item);
+        }
+        Ok(tree)
+    }
+
+    /// Build a tree from all items at once.
+    /// All points are checked before the tree is built.
+    ///
+    /// Items are distributed top down. Each node keeps items of its most crowded quadrant and passes the rest on.
+    /// Nodes are split at the center of their boundary like on insertion, not at the median of their items,
+    /// so the tree can be changed afterwards like any other tree.
+    /// Prefer this over inserting one by one for data which is known up front and rarely changed,
+    /// e.g. sorted or clustered data which would otherwise produce deep nodes along the insertion order.
+    /// All items are held in memory while the tree is built.
+    /// # Errors
+    /// Returns an error for the first point that is out of bounds. In this case no tree is built.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let boundary = Boundary::between_points((0,0), (16,16));
+    /// let items = (0..16).map(|i| ((i, i).into(), i)).collect::<Vec<_>>();
+    /// let tree = QuadTree::<_,_,ConstCap<4>>::bulk_load(boundary, ConstCap, items).unwrap();
+    /// assert_eq!(tree.len(), 16);
+    /// assert_eq!(tree.depth(), 2);
+    /// ```
+    pub fn bulk_load(
+        boundary: Boundary<C>,
+        capacity: Cap,
+        items: impl IntoIterator<Item = (Point<C>, Item)>,
+    ) -> Result<Self, QuadTreeError<C>> {
+        let items = items.into_iter().collect::<Vec<_>>();
+        if let Some((point, _)) = items.iter().find(|(p, _)| !boundary.contains(p)) {
+            return Err(QuadTreeError::OutOfBounds(boundary, *point));
+        }
+        let mut tree = Self::new_with_capacity(boundary, capacity);
+        tree.fill_balanced(items);
+        Ok(tree)
+    }
+
+    fn fill_sorted(&mut self, mut items: Vec<(Point<C>, Item)>) {
+        let mut fits = self.capacity.has_room_for_all(&items);
+        while !fits && self.capacity.grow() {
+            fits = self.capacity.has_room_for_all(&items);
+        }
+        // Identical points can not be separated by splitting
+        let is_single_point = items.iter().all(|(p, _)| *p == items[0].0);
+        let boundaries = match self.boundary.split() {
+            Some(boundaries) if !fits && !is_single_point => boundaries,
+            _ => {
+                if !items.is_empty() {
+                    self.items = Some(items);
+                }
+                return;
+            }
+        };
+        let index = |(p, _): &(Point<C>, Item)| quadrant_index(&boundaries[0], 4, p);
+        debug_assert!(
+            items.windows(2).all(|w| index(&w[0]) <= index(&w[1])),
+            "items must be sorted in morton order"
+        );
+        let capacity = self.capacity.for_new_node();
+        let mut quads = boundaries.map(|b| self.new_child(b, capacity));
+        for (i, quad) in quads.iter_mut().enumerate().rev() {
+            let start = items.partition_point(|item| index(item) < i);
+            quad.fill_sorted(items.split_off(start));
+        }
+        self.quadrants = Some(Box::new(quads) as Box<[_]>);
+    }
+
+    /// Rebuild the tree from all of its items.
+    ///
+    /// All items are drained and inserted again in Morton order (Z-order) relative to the boundary of the tree.
+    /// Neighbouring items are inserted together, so they fill the nodes on their way down before others do,
+    /// which tends to produce a shallower tree than an insertion order that adds dense clusters last.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (16,16)));
+    /// for p in [(15,15), (1,15), (15,1), (1,1), (2,2), (3,3)] {
+    ///     tree.insert_at(p, ());
+    /// }
+    /// let depth = tree.depth();
+    /// tree.rebalance();
+    /// assert!(tree.depth() < depth);
+    /// assert_eq!(tree.len(), 6);
+    /// ```
+    pub fn rebalance(&mut self) {
+        self.generation.bump();
+        let mut items = self.take_all_items();
+        self.capacity = self.capacity.for_new_node();
+        items.sort_by_cached_key(|(point, _)| morton::index(&self.boundary, point));
+        for (point, item) in items {
+            self.insert_tracked_unchecked(point, item);
+        }
+    }
+
+    /// Removes and returns all items, leaving an empty tree
+    fn take_all_items(&mut self) -> Vec<(Point<C>, Item)> {
+        // The root is emptied in place so it keeps its own settings
+        let mut items = self.items.take().unwrap_or_default();
+        let mut stack = self
+            .quadrants
+            .take()
+            .map(|q| q.into_vec())
+            .unwrap_or_default();
+        while let Some(node) = stack.pop() {
+            items.extend(node.items.into_iter().flatten());
+            stack.extend(node.quadrants.into_iter().flat_map(|q| q.into_vec()));
+        }
+        items
+    }
+
+    fn fill_balanced(&mut self, items: Vec<(Point<C>, Item)>) {
+        let mut fits = self.capacity.has_room_for_all(&items);
+        while !fits && self.capacity.grow() {
+            fits = self.capacity.has_room_for_all(&items);
+        }
+        // Identical points can not be separated by splitting
+        let is_single_point = items.iter().all(|(p, _)| *p == items[0].0);
+        let boundaries = match self.split_boundaries() {
+            Some(boundaries) if !fits && !is_single_point => boundaries,
+            _ => {
+                if !items.is_empty() {
+                    self.items = Some(items);
+                }
+                return;
+            }
+        };
+        let mut groups = boundaries.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        for item in items {
+            groups[quadrant_index(&boundaries[0], boundaries.len(), &item.0)].push(item);
+        }
+        let mut kept = Vec::new();
+        while let Some(crowded) = groups.iter_mut().max_by_key(|g| g.len()) {
+            match crowded.last() {
+                Some((_, item)) if self.capacity.has_room(&kept, item) => {
+                    kept.extend(crowded.pop());
+                }
+                _ => break,
+            }
+        }
+        if !kept.is_empty() {
+            self.items = Some(kept);
+        }
+        let capacity = self.capacity.for_new_node();
+        let mut quads = boundaries
+            .into_iter()
+            .map(|b| self.new_child(b, capacity))
+            .collect::<Box<[_]>>();
+        for (quad, group) in quads.iter_mut().zip(groups) {
+            quad.fill_balanced(group);
+        }
+        self.quadrants = Some(quads);
+        self.collapse_empty_quadrants();
+    }
+
+    /// Insert all items or none of them.
+    /// All points are checked before the first item is inserted.
+    /// # Errors
+    /// Returns an error for the first point that is out of bounds. In this case the tree is left unchanged.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// assert!(tree.insert_batch_checked(&[((1,1).into(), 1), ((11,11).into(), 2)]).is_err());
+    /// assert_eq!(tree.iter().count(), 0);
+    /// assert!(tree.insert_batch_checked(&[((1,1).into(), 1), ((5,5).into(), 2)]).is_ok());
+    /// assert_eq!(tree.iter().count(), 2);
+    /// ```
+    pub fn insert_batch_checked(
+        &mut self,
+        items: &[(Point<C>, Item)],
+    ) -> Result<(), QuadTreeError<C>>
+    where
+        Item: Clone,
+    {
+        self.generation.bump();
+        if let Some((point, _)) = items.iter().find(|(p, _)| !self.boundary.contains(p)) {
+            return Err(QuadTreeError::OutOfBounds(self.boundary, *point));
+        }
+        for (point, item) in items {
+            self.insert_at_unchecked(*point, item.clone());
+        }
+        Ok(())
+    }
+
+    /// Insert all items of an iterator without checking their points, like [`QuadTree::insert_at_unchecked`].
+    /// Use [`QuadTree::try_extend_at`] or the [`Extend`] implementation if points may be out of bounds.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.extend_at([((1,1), 1), ((9,2), 2), ((5,5), 3)]);
+    /// assert_eq!(tree.len(), 3);
+    /// ```
+    pub fn extend_at<P>(&mut self, iter: impl IntoIterator<Item = (P, Item)>)
+    where
+        P: IntoPoint<C>,
+    {
+        self.generation.bump();
+        for (point, item) in iter {
+            self.insert_tracked_unchecked(point.into_point(), item);
         }
     }
 
+    /// Insert all items of an iterator.
+    /// Every point is checked, items which are out of bounds or invalid are not inserted and returned in order instead.
+    /// Use [`QuadTree::insert_batch_checked`] to insert either all items or none of them.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// let rejected = tree.try_extend_at([((1,1), 1), ((11,11), 2), ((5,5), 3)]);
+    /// assert_eq!(rejected, vec![((11,11).into(), 2)]);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn try_extend_at<P>(
+        &mut self,
+        iter: impl IntoIterator<Item = (P, Item)>,
+    ) -> Vec<(Point<C>, Item)>
+    where
+        P: IntoPoint<C>,
+    {
+        self.generation.bump();
+        iter.into_iter()
+            .filter_map(|(point, item)| {
+                let point = point.into_point();
+                if point.is_valid() && self.boundary.contains(&point) {
+                    self.insert_tracked_unchecked(point, item);
+                    None
+                } else {
+                    Some((point, item))
+                }
+            })
+            .collect()
+    }
+
     /// Get all items in a given area.
+    ///
+    /// Results are yielded in a spatially coherent order. All items of a quadrant and its
+    /// descendants are yielded before any item of the next quadrant. Quadrants are visited
+    /// top left, top right, bottom left, bottom right. Items stored in a node itself are
+    /// yielded after the items of its quadrants.
     /// # Example
     /// ```
     /// use qutee::*;
@@ -208,6 +920,85 @@ where
         Query::new(self, area)
     }
 
+    /// Get items in a given area while visiting at most `max_nodes` nodes.
+    /// The returned flag is `true` if the traversal stopped early and the result may be incomplete.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,2), "root");
+    /// tree.insert_at((7,2), "east");
+    /// tree.insert_at((7,7), "south-east");
+    /// // A budget of one node only covers the root
+    /// let (res, truncated) = tree.query_budgeted(Boundary::between_points((0,0), (10,10)), 1);
+    /// assert_eq!(res, vec![&"root"]);
+    /// assert!(truncated);
+    /// let (res, truncated) = tree.query_budgeted(Boundary::between_points((0,0), (10,10)), 5);
+    /// assert_eq!(res.len(), 3);
+    /// assert!(!truncated);
+    /// ```
+    pub fn query_budgeted<A>(&self, area: A, max_nodes: usize) -> (Vec<&Item>, bool)
+    where
+        A: Area<C>,
+    {
+        let mut res = Vec::new();
+        let mut stack = vec![self];
+        let mut visited = 0;
+        while let Some(node) = stack.pop() {
+            if visited == max_nodes {
+                return (res, true);
+            }
+            visited += 1;
+            res.extend(
+                node.items
+                    .iter()
+                    .flatten()
+                    .filter(|(point, _)| area.contains(point))
+                    .map(|(_, item)| item),
+            );
+            stack.extend(
+                node.quadrants
+                    .iter()
+                    .flat_map(|q| q.iter().rev())
+                    .filter(|q| area.intersects(&q.boundary)),
+            );
+        }
+        (res, false)
+    }
+
+    /// Get all items in a given area in chunks of `chunk_size` items.
+    /// Only the last chunk may be smaller.
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// for x in 1..=5 {
+    ///     tree.insert_at((x,5), x);
+    /// }
+    /// let chunks = tree.query_chunks(Boundary::between_points((0,0), (10,10)), 2).collect::<Vec<_>>();
+    /// assert_eq!(chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(), vec![2, 2, 1]);
+    /// ```
+    pub fn query_chunks<'a, A>(
+        &'a self,
+        area: A,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Vec<&'a Item>> + 'a
+    where
+        A: Area<C> + 'a,
+    {
+        assert!(chunk_size > 0, "chunk size must be greater than 0");
+        // No chunk can hold more items than the tree
+        let chunk_capacity = chunk_size.min(self.len());
+        let mut query = self.query(area);
+        std::iter::from_fn(move || {
+            let mut chunk = Vec::with_capacity(chunk_capacity);
+            chunk.extend(query.by_ref().take(chunk_size));
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
     /// Get all items in a given area and their coordinates.
     /// # Example
     /// ```
@@ -231,214 +1022,3604 @@ where
         QueryPoints::new(self, area)
     }
 
-    /// Get an iterator over all items.
-    pub fn iter(&self) -> Iter<'_, C, Item, Cap> {
-        Iter::new(self)
+    /// Get copies of all items in a given area and their coordinates.
+    /// The results do not borrow the tree, so they can be kept after the tree is modified.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((4,4), "spawn");
+    /// tree.insert_at((9,9), "exit");
+    /// let snapshot = tree.query_copied_points(Boundary::between_points((0,0), (5,5))).collect::<Vec<_>>();
+    /// tree.clear();
+    /// assert_eq!(snapshot, vec![((4,4).into(), "spawn")]);
+    /// ```
+    pub fn query_copied_points<'a, A>(
+        &'a self,
+        area: A,
+    ) -> impl Iterator<Item = (Point<C>, Item)> + 'a
+    where
+        A: Area<C> + 'a,
+        Item: Copy,
+    {
+        self.query_points(area).copied()
     }
 
-    /// Get an iterator over all items and their coordinates.
-    pub fn iter_points(&self) -> IterPoints<'_, C, Item, Cap> {
-        IterPoints::new(self)
+    /// Get all items in a given area together with statistics about the traversal.
+    /// Only available with the `profiling` feature.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 1);
+    /// tree.insert_at((4,6), 2);
+    /// tree.insert_at((8,8), 3);
+    /// // Nodes inside the area are returned without checking their items
+    /// let (res, profile) = tree.query_profiled(Boundary::between_points((0,0), (10,10)));
+    /// assert_eq!(res.len(), 3);
+    /// assert_eq!(profile.items_tested, 0);
+    /// let (res, profile) = tree.query_profiled(Boundary::between_points((0,0), (5,5)));
+    /// assert_eq!(res, vec![&1]);
+    /// assert!(profile.items_tested > profile.items_returned);
+    /// ```
+    #[cfg(feature = "profiling")]
+    pub fn query_profiled<A>(&self, area: A) -> (Vec<&Item>, QueryProfile)
+    where
+        A: Area<C>,
+    {
+        profile::query_profiled(self, &area)
+    }
+
+    /// Get all items in a given area.
+    /// Up to `N` items are stored inline, so small results do not allocate.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 1);
+    /// tree.insert_at((2,2), 2);
+    /// tree.insert_at((3,3), 3);
+    /// let area = Boundary::between_points((0,0), (5,5));
+    /// assert!(!tree.query_smallvec::<_, 4>(area).spilled());
+    /// assert!(tree.query_smallvec::<_, 2>(area).spilled());
+    /// ```
+    #[cfg(feature = "smallvec")]
+    pub fn query_smallvec<A, const N: usize>(&self, area: A) -> smallvec::SmallVec<[&Item; N]>
+    where
+        A: Area<C>,
+    {
+        self.query(area).collect()
+    }
+
+    /// Call `f` for every item in a given area.
+    /// Unlike `query` this does not allocate.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,3), 120);
+    /// tree.insert_at((4,6), 80);
+    /// tree.insert_at((9,9), 500);
+    /// let mut population = 0;
+    /// tree.for_each_in_area(Boundary::between_points((0,0), (5,7)), |_, town| population += town);
+    /// assert_eq!(population, 200);
+    /// ```
+    pub fn for_each_in_area<A, F>(&self, area: A, mut f: F)
+    where
+        A: Area<C>,
+        F: FnMut(&Point<C>, &Item),
+    {
+        self.for_each_in_area_rec(
+            &area,
+            boundary::area_encloses(&area, &self.boundary),
+            &mut f,
+        );
+    }
+
+    /// Clear `out` and fill it with all items in a given area.
+    /// Reusing `out` for many queries avoids allocating for every query.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), "a");
+    /// tree.insert_at((8,2), "b");
+    /// tree.insert_at((8,8), "c");
+    /// let mut out = Vec::new();
+    /// tree.query_into_vec(Boundary::between_points((5,0), (10,5)), &mut out);
+    /// assert_eq!(out, [&"b"]);
+    /// tree.query_into_vec(Boundary::between_points((5,5), (10,10)), &mut out);
+    /// assert_eq!(out, [&"c"]);
+    /// ```
+    pub fn query_into_vec<'a, A>(&'a self, area: A, out: &mut Vec<&'a Item>)
+    where
+        A: Area<C>,
+    {
+        out.clear();
+        self.for_each_in_area_rec(
+            &area,
+            boundary::area_encloses(&area, &self.boundary),
+            &mut |_, item| out.push(item),
+        );
+    }
+
+    fn for_each_in_area_rec<'a, A, F>(&'a self, area: &A, is_enclosed_by_area: bool, f: &mut F)
+    where
+        A: Area<C>,
+        F: FnMut(&'a Point<C>, &'a Item),
+    {
+        for (point, item) in self.items.iter().flatten() {
+            if is_enclosed_by_area || area.contains(point) {
+                f(point, item);
+            }
+        }
+        for quad in self.quadrants.iter().flat_map(|q| q.iter()) {
+            if is_enclosed_by_area {
+                quad.for_each_in_area_rec(area, true, f);
+            } else if area.intersects(&quad.boundary) {
+                quad.for_each_in_area_rec(area, boundary::area_encloses(area, &quad.boundary), f);
+            }
+        }
+    }
+
+    /// Sum `weight` over the points of all items in a given area.
+    /// With a weight depending on the distance to a center this gives a weighted density, e.g. for heat maps.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((5,5), ());
+    /// tree.insert_at((6,5), ());
+    /// tree.insert_at((9,9), ());
+    /// // Points count less the further they are from (5,5)
+    /// let heat = |p: &Point<i32>| 1. / f64::from(1 + (p.x - 5).abs() + (p.y - 5).abs());
+    /// assert_eq!(tree.weighted_count(Boundary::between_points((4,4), (6,6)), heat), 1.5);
+    /// assert_eq!(tree.weighted_count(Boundary::between_points((4,4), (6,6)), |_| 1.), 2.);
+    /// ```
+    pub fn weighted_count<A>(&self, area: A, weight: impl Fn(&Point<C>) -> f64) -> f64
+    where
+        A: Area<C>,
+    {
+        let mut sum = 0.;
+        self.for_each_in_area(area, |point, _| sum += weight(point));
+        sum
+    }
+
+    /// Fold all items in a given area, stopping at the first error.
+    /// This is the spatial counterpart of [`Iterator::try_fold`].
+    /// # Errors
+    /// Returns the first error returned by `f`. No further items are visited after that.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 200u8);
+    /// tree.insert_at((2,2), 50);
+    /// tree.insert_at((8,8), 100);
+    /// let add = |sum: u8, item: &u8| sum.checked_add(*item).ok_or("overflow");
+    /// assert_eq!(tree.try_fold_in_area(Boundary::between_points((0,0), (5,5)), 0, add), Ok(250));
+    /// assert_eq!(tree.try_fold_in_area(Boundary::between_points((0,0), (10,10)), 0, add), Err("overflow"));
+    /// ```
+    pub fn try_fold_in_area<A, B, E>(
+        &self,
+        area: A,
+        init: B,
+        mut f: impl FnMut(B, &Item) -> Result<B, E>,
+    ) -> Result<B, E>
+    where
+        A: Area<C>,
+    {
+        self.try_fold_in_area_rec(
+            &area,
+            boundary::area_encloses(&area, &self.boundary),
+            init,
+            &mut f,
+        )
+    }
+
+    fn try_fold_in_area_rec<A, B, E, F>(
+        &self,
+        area: &A,
+        is_enclosed_by_area: bool,
+        mut acc: B,
+        f: &mut F,
+    ) -> Result<B, E>
+    where
+        A: Area<C>,
+        F: FnMut(B, &Item) -> Result<B, E>,
+    {
+        for (point, item) in self.items.iter().flatten() {
+            if is_enclosed_by_area || area.contains(point) {
+                acc = f(acc, item)?;
+            }
+        }
+        for quad in self.quadrants.iter().flat_map(|q| q.iter()) {
+            if is_enclosed_by_area {
+                acc = quad.try_fold_in_area_rec(area, true, acc, f)?;
+            } else if area.intersects(&quad.boundary) {
+                acc = quad.try_fold_in_area_rec(
+                    area,
+                    boundary::area_encloses(area, &quad.boundary),
+                    acc,
+                    f,
+                )?;
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Get all items in a floating point area.
+    /// Points are converted to `f64` before they are tested, so an integer tree can be queried with fractional bounds.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), "a");
+    /// tree.insert_at((4,5), "b");
+    /// let res = tree.query_f64(Boundary::between_points((2.5,4.5), (3.5,5.5)));
+    /// assert_eq!(res, vec![&"a"]);
+    /// ```
+    pub fn query_f64(&self, area: Boundary<f64>) -> Vec<&Item> {
+        let mut res = Vec::new();
+        self.query_f64_rec(&area, &mut res);
+        res
+    }
+
+    fn query_f64_rec<'a>(&'a self, area: &Boundary<f64>, res: &mut Vec<&'a Item>) {
+        let is_enclosed_by_area = area.encloses(&self.boundary.to_f64());
+        for (point, item) in self.items.iter().flatten() {
+            if is_enclosed_by_area
+                || area.contains_xy(boundary::to_f64(point.x), boundary::to_f64(point.y))
+            {
+                res.push(item);
+            }
+        }
+        for quad in self.quadrants.iter().flat_map(|q| q.iter()) {
+            if area.intersects(&quad.boundary.to_f64()) {
+                quad.query_f64_rec(area, res);
+            }
+        }
+    }
+
+    /// Get the smallest boundary containing all items in a given area.
+    /// Returns `None` if no item is inside the area.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,3), ());
+    /// tree.insert_at((4,8), ());
+    /// tree.insert_at((6,5), ());
+    /// tree.insert_at((9,1), ());
+    /// assert_eq!(tree.query_bounds(Boundary::between_points((1,1), (7,9))), Some(Boundary::between_points((2,3), (6,8))));
+    /// assert_eq!(tree.query_bounds(Boundary::between_points((8,8), (10,10))), None);
+    /// ```
+    pub fn query_bounds<A>(&self, area: A) -> Option<Boundary<C>>
+    where
+        A: Area<C>,
+    {
+        Boundary::try_from_points(self.query_points(area).map(|(point, _)| *point))
+    }
+
+    /// Get all items in a given area as a structure of arrays.
+    /// The returned vectors contain the x coordinates, y coordinates and items at matching indices.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,2), 'a');
+    /// tree.insert_at((3,4), 'b');
+    /// tree.insert_at((9,9), 'c');
+    /// let (xs, ys, items) = tree.query_soa(Boundary::between_points((0,0), (5,5)));
+    /// assert_eq!(xs, vec![1, 3]);
+    /// assert_eq!(ys, vec![2, 4]);
+    /// assert_eq!(items, vec![&'a', &'b']);
+    /// ```
+    pub fn query_soa<A>(&self, area: A) -> (Vec<C>, Vec<C>, Vec<&Item>)
+    where
+        A: Area<C>,
+    {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        let mut items = Vec::new();
+        for (point, item) in self.query_points(area) {
+            xs.push(point.x);
+            ys.push(point.y);
+            items.push(item);
+        }
+        (xs, ys, items)
+    }
+
+    /// Get at most `limit` items in a given area.
+    /// Space for `limit` items, but never more than the tree holds, is allocated upfront.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// for x in 0..4 {
+    ///     tree.insert_at((x * 3, 5), x);
+    /// }
+    /// let area = Boundary::between_points((0,0), (10,10));
+    /// assert_eq!(tree.query_limited(area, 2).len(), 2);
+    /// assert_eq!(tree.query_limited(area, 10).len(), 4);
+    /// ```
+    pub fn query_limited<A>(&self, area: A, limit: usize) -> Vec<&Item>
+    where
+        A: Area<C>,
+    {
+        let mut res = Vec::with_capacity(limit.min(self.len()));
+        res.extend(self.query(area).take(limit));
+        res
+    }
+
+    /// Get all items in a given area together with their euclidean distance to `focus`.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,4), 1);
+    /// tree.insert_at((9,9), 2);
+    /// let res = tree.query_with_distance(Boundary::between_points((0,0), (5,5)), (0,0)).collect::<Vec<_>>();
+    /// assert_eq!(res, vec![(&1, 5.)]);
+    /// ```
+    pub fn query_with_distance<A>(
+        &self,
+        area: A,
+        focus: impl IntoPoint<C>,
+    ) -> impl Iterator<Item = (&Item, f64)>
+    where
+        A: Area<C>,
+    {
+        let focus = focus.into_point();
+        self.query_points(area)
+            .map(move |(point, item)| (item, Euclidean::distance(&focus, point)))
+    }
+
+    /// Get all items inside `include` which are not inside `exclude`.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((5,5), "center");
+    /// tree.insert_at((4,6), "near");
+    /// tree.insert_at((1,9), "corner");
+    /// let ring = tree.query_except(Boundary::between_points((0,0), (10,10)), Boundary::between_points((3,3), (7,7))).collect::<Vec<_>>();
+    /// assert_eq!(ring, vec![&"corner"]);
+    /// ```
+    pub fn query_except<A, B>(&self, include: A, exclude: B) -> impl Iterator<Item = &Item>
+    where
+        A: Area<C>,
+        B: Area<C>,
+    {
+        self.query_points(include)
+            .filter(move |(point, _)| !exclude.contains(point))
+            .map(|(_, item)| item)
+    }
+
+    /// Get all items in a given area, skipping the per item check for small nodes.
+    ///
+    /// All items of a node whose area is smaller than `min_node_area` and which intersects `area` are returned
+    /// without checking them against `area`. The result may therefore contain false positives close to the
+    /// edge of `area`, but never misses an item. This is faster for dense trees viewed at a coarse scale.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 1);
+    /// tree.insert_at((4,4), 2);
+    /// tree.insert_at((9,9), 3);
+    /// let area = Boundary::between_points((0,0), (3,3));
+    /// assert_eq!(tree.query_approx_coarse(area, 0).collect::<Vec<_>>(), vec![&1]);
+    /// // The top left quadrant is smaller than 50, so all of its items are returned
+    /// assert_eq!(tree.query_approx_coarse(area, 50).collect::<Vec<_>>(), vec![&1, &2]);
+    /// ```
+    pub fn query_approx_coarse<A>(&self, area: A, min_node_area: C) -> impl Iterator<Item = &Item>
+    where
+        A: Area<C>,
+    {
+        // Areas are compared as f64, so large integer boundaries can not overflow
+        let min_node_area = boundary::to_f64(min_node_area);
+        let mut stack = Vec::new();
+        if area.intersects(&self.boundary) {
+            stack.push((self, false));
+        }
+        let mut items: &[(Point<C>, Item)] = &[];
+        let mut take_all = false;
+        std::iter::from_fn(move || loop {
+            if let Some(((point, item), rest)) = items.split_first() {
+                items = rest;
+                if take_all || area.contains(point) {
+                    return Some(item);
+                }
+                continue;
+            }
+            let (node, parent_takes_all) = stack.pop()?;
+            take_all = parent_takes_all
+                || boundary::area_encloses(&area, &node.boundary)
+                || node.boundary.area_f64() < min_node_area;
+            items = node.items.as_deref().unwrap_or_default();
+            stack.extend(
+                node.quadrants
+                    .iter()
+                    .flat_map(|q| q.iter().rev())
+                    .filter(|q| take_all || area.intersects(&q.boundary))
+                    .map(|q| (q, take_all)),
+            );
+        })
+    }
+
+    /// Get the boundaries of all leaf nodes which intersect a given area.
+    /// Items are not checked, so this is a cheap way to find the cells an area touches.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 1);
+    /// tree.insert_at((8,8), 2);
+    /// let leaves = tree.query_leaves(Boundary::between_points((0,0), (2,2))).collect::<Vec<_>>();
+    /// assert_eq!(leaves, vec![&Boundary::between_points((0,0), (5,5))]);
+    /// ```
+    pub fn query_leaves<A>(&self, area: A) -> impl Iterator<Item = &Boundary<C>>
+    where
+        A: Area<C>,
+    {
+        let mut stack = Vec::new();
+        if area.intersects(&self.boundary) {
+            stack.push(self);
+        }
+        std::iter::from_fn(move || {
+            while let Some(node) = stack.pop() {
+                match &node.quadrants {
+                    Some(quads) => {
+                        stack.extend(quads.iter().rev().filter(|q| area.intersects(&q.boundary)))
+                    }
+                    None => return Some(&node.boundary),
+                }
+            }
+            None
+        })
+    }
+
+    /// Get all items in a given area for which `predicate` returns `true`.
+    /// Items are filtered while the tree is traversed, no intermediate collection is allocated.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,2), ("ally", 1));
+    /// tree.insert_at((3,4), ("enemy", 2));
+    /// tree.insert_at((8,8), ("enemy", 3));
+    /// let res = tree.query_filter(Boundary::between_points((0,0), (5,5)), |(kind, _)| *kind == "enemy").collect::<Vec<_>>();
+    /// assert_eq!(res, vec![&("enemy", 2)]);
+    /// ```
+    pub fn query_filter<'a, A, F>(&'a self, area: A, predicate: F) -> impl Iterator<Item = &'a Item>
+    where
+        A: Area<C> + 'a,
+        F: Fn(&Item) -> bool + 'a,
+    {
+        self.query(area).filter(move |item| predicate(item))
+    }
+
+    /// Get all items in a given area which are contained in `wanted`.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// use std::collections::HashSet;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,2), 10);
+    /// tree.insert_at((3,4), 11);
+    /// tree.insert_at((8,8), 12);
+    /// let selected = HashSet::from([11, 12]);
+    /// let res = tree.query_values(Boundary::between_points((0,0), (5,5)), &selected).collect::<Vec<_>>();
+    /// assert_eq!(res, vec![&11]);
+    /// ```
+    pub fn query_values<'a, A, S>(
+        &'a self,
+        area: A,
+        wanted: &'a HashSet<Item, S>,
+    ) -> impl Iterator<Item = &'a Item>
+    where
+        A: Area<C>,
+        Item: Hash + Eq,
+        S: BuildHasher,
+    {
+        self.query(area).filter(move |item| wanted.contains(item))
+    }
+
+    /// Get all distinct items in a given area.
+    /// An item stored at several points, e.g. to cover all cells it spans, is only returned once.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), "wall");
+    /// tree.insert_at((4,5), "wall");
+    /// tree.insert_at((7,3), "door");
+    /// let res = tree.query_dedup(Boundary::between_points((0,0), (10,10))).collect::<Vec<_>>();
+    /// assert_eq!(res.len(), 2);
+    /// ```
+    pub fn query_dedup<A>(&self, area: A) -> impl Iterator<Item = &Item>
+    where
+        A: Area<C>,
+        Item: Hash + Eq,
+    {
+        let mut seen = HashSet::new();
+        self.query(area).filter(move |item| seen.insert(*item))
+    }
+
+    /// Same as `query_points` except that the coordinates are converted into a custom point type.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// struct Vec2(f32, f32);
+    /// impl FromPoint<f32> for Vec2 {
+    ///     fn from_point(p: Point<f32>) -> Self {
+    ///         Vec2(p.x, p.y)
+    ///     }
+    /// }
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0.,0.), (10.,10.)));
+    /// tree.insert_at((3.,5.), 1);
+    /// let (Vec2(x, y), item) = tree.query_points_as::<Vec2, _>(Boundary::between_points((2.,1.), (8.,9.))).next().unwrap();
+    /// assert_eq!((x, y, item), (3., 5., &1));
+    /// ```
+    pub fn query_points_as<P, A>(&self, area: A) -> impl Iterator<Item = (P, &Item)>
+    where
+        P: FromPoint<C>,
+        A: Area<C>,
+    {
+        self.query_points(area)
+            .map(|(point, item)| (P::from_point(*point), item))
+    }
+
+    /// Get all items in a given area ordered by their position along a Hilbert curve covering the tree.
+    /// Items close to each other in the result are also close in space, which is useful for rendering or serialization.
+    /// Items with the same position on the curve keep the order of [`QuadTree::query`].
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((9,1), 1);
+    /// tree.insert_at((1,1), 2);
+    /// tree.insert_at((1,9), 3);
+    /// tree.insert_at((9,9), 4);
+    /// assert_eq!(tree.query_hilbert(Boundary::between_points((0,0), (10,10))), vec![&2, &3, &4, &1]);
+    /// ```
+    pub fn query_hilbert<A>(&self, area: A) -> Vec<&Item>
+    where
+        A: Area<C>,
+    {
+        let mut res = self
+            .query_points(area)
+            .map(|(point, item)| (hilbert::index(&self.boundary, point), item))
+            .collect::<Vec<_>>();
+        res.sort_by_key(|(index, _)| *index);
+        res.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Get the item closest to a point using the euclidean distance.
+    /// Ties are broken arbitrarily. Returns `None` if the tree is empty.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// assert_eq!(tree.nearest_to((8,8)), None);
+    /// tree.insert_at((3,5), 1);
+    /// tree.insert_at((7,3), 2);
+    /// assert_eq!(tree.nearest_to((8,8)), Some(&2));
+    /// ```
+    pub fn nearest_to(&self, point: impl IntoPoint<C>) -> Option<&Item> {
+        self.nearest_with::<Euclidean>(point)
+    }
+
+    /// Get the item closest to a point using a given distance metric.
+    /// Returns `None` if the tree is empty.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,3), "diagonal");
+    /// tree.insert_at((0,5), "straight");
+    /// assert_eq!(tree.nearest_with::<Manhattan>((0,0)), Some(&"straight"));
+    /// assert_eq!(tree.nearest_with::<Chebyshev>((0,0)), Some(&"diagonal"));
+    /// ```
+    pub fn nearest_with<M>(&self, point: impl IntoPoint<C>) -> Option<&Item>
+    where
+        M: Metric<C>,
+    {
+        nearest::k_nearest::<M, _, _, _>(self, &point.into_point(), 1, f64::INFINITY)
+            .first()
+            .map(|(_, (_, item))| item)
+    }
+
+    /// Get up to `k` items closest to a point.
+    /// Distances are euclidean, results are sorted nearest first. Ties are broken arbitrarily.
+    /// If the tree holds fewer than `k` items, all of them are returned.
+    ///
+    /// Nodes are visited best first and skipped once they can not hold an item closer than the current `k`-th item.
+    /// For evenly distributed points this visits `O(log n + k)` nodes, the `k` best items are kept in a heap,
+    /// which adds `O(log k)` per item considered.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1);
+    /// tree.insert_at((7,3), 2);
+    /// tree.insert_at((9,9), 3);
+    /// assert_eq!(tree.k_nearest((8,3), 2), vec![&2, &1]);
+    /// assert_eq!(tree.k_nearest((8,3), 5), vec![&2, &1, &3]);
+    /// ```
+    pub fn k_nearest(&self, point: impl IntoPoint<C>, k: usize) -> Vec<&Item> {
+        nearest::k_nearest::<Euclidean, _, _, _>(self, &point.into_point(), k, f64::INFINITY)
+            .into_iter()
+            .map(|(_, (_, item))| item)
+            .collect()
+    }
+
+    /// Get up to `k` items closest to a point which are at most `max_radius` away.
+    /// Distances are euclidean, results are sorted nearest first.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1);
+    /// tree.insert_at((7,3), 2);
+    /// tree.insert_at((9,9), 3);
+    /// assert_eq!(tree.k_nearest_within((8,3), 5, 6), vec![&2, &1]);
+    /// ```
+    pub fn k_nearest_within(
+        &self,
+        point: impl IntoPoint<C>,
+        k: usize,
+        max_radius: C,
+    ) -> Vec<&Item> {
+        nearest::k_nearest::<Euclidean, _, _, _>(
+            self,
+            &point.into_point(),
+            k,
+            boundary::to_f64(max_radius),
+        )
+        .into_iter()
+        .map(|(_, (_, item))| item)
+        .collect()
+    }
+
+    /// Get an iterator over all items.
+    pub fn iter(&self) -> Iter<'_, C, Item, Cap> {
+        Iter::new(self)
+    }
+
+    /// Get an iterator over mutable references to all items.
+    ///
+    /// The points of the items can not be changed. Items implementing [`AsPoint`] must not be changed in a way
+    /// which affects their point, otherwise [`QuadTree::insert_keyed`] and [`QuadTree::remove_item`] can no longer find them.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,2), 40);
+    /// tree.insert_at((8,8), 95);
+    /// tree.iter_mut().for_each(|health| *health = (*health + 10).min(100));
+    /// let mut health = tree.iter().collect::<Vec<_>>();
+    /// health.sort();
+    /// assert_eq!(health, vec![&50, &100]);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, C, Item, Cap> {
+        self.generation.bump();
+        IterMut::new(self)
+    }
+
+    /// Get an iterator over all items and the quadrant indices leading from the root to the node holding them.
+    /// Quadrants are numbered `0` top left, `1` top right, `2` bottom left and `3` bottom right.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1);
+    /// tree.insert_at((8,8), 2);
+    /// let res = tree.iter_with_path().collect::<Vec<_>>();
+    /// assert_eq!(res, vec![(vec![], &1), (vec![3], &2)]);
+    /// ```
+    pub fn iter_with_path(&self) -> IterWithPath<'_, C, Item, Cap> {
+        IterWithPath::new(self)
+    }
+
+    /// Get an iterator over all items and their coordinates.
+    pub fn iter_points(&self) -> IterPoints<'_, C, Item, Cap> {
+        IterPoints::new(self)
+    }
+
+    /// Get an iterator over all items whose point matches `pred`.
+    /// Every item is checked, no part of the tree is skipped.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,2), "a");
+    /// tree.insert_at((3,7), "b");
+    /// tree.insert_at((6,6), "c");
+    /// let mut diagonal = tree.iter_points_where(|p| p.x == p.y).collect::<Vec<_>>();
+    /// diagonal.sort();
+    /// assert_eq!(diagonal, vec![&"a", &"c"]);
+    /// ```
+    pub fn iter_points_where<'a, F>(&'a self, pred: F) -> impl Iterator<Item = &'a Item> + 'a
+    where
+        F: Fn(&Point<C>) -> bool + 'a,
+    {
+        self.iter_points()
+            .filter(move |(point, _)| pred(point))
+            .map(|(_, item)| item)
+    }
+
+    /// Count the items in each cell of a grid dividing the boundary of the tree into `cols` × `rows` cells.
+    /// The result is indexed by row first and column second.
+    /// Points on a border between two cells are counted in the cell to the right or below.
+    /// Points on the right or bottom border of the tree are counted in the last column or row.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), ());
+    /// tree.insert_at((2,3), ());
+    /// tree.insert_at((8,2), ());
+    /// tree.insert_at((5,7), ());
+    /// tree.insert_at((10,10), ());
+    /// assert_eq!(tree.histogram(2, 2), vec![vec![2, 1], vec![0, 2]]);
+    /// ```
+    pub fn histogram(&self, cols: usize, rows: usize) -> Vec<Vec<usize>> {
+        let mut histogram = vec![vec![0; cols]; rows];
+        for (point, _) in self.iter_points() {
+            if let Some((col, row)) = self.boundary.grid_index(point, cols, rows) {
+                histogram[row][col] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Returns the boundary of this QuadTree
+    pub fn boundary(&self) -> &Boundary<C> {
+        &self.boundary
+    }
+
+    /// Write the tree to `w` using a simple binary format.
+    ///
+    /// The format contains a tag for the coordinate encoding, the boundary, the capacity, the maximum depth,
+    /// a flag byte for adaptive splits and preallocated items, the number of items and one record per item.
+    /// Each record holds the coordinates followed by the length prefixed bytes returned by `serialize`.
+    /// All numbers are little endian. Integer coordinates are stored as `i128` and all other coordinates as `f64`,
+    /// so coordinates of the primitive types are stored without losing precision.
+    /// # Errors
+    /// Returns an error if writing to `w` fails, an integer coordinate does not fit into an `i128`
+    /// or the capacity is larger than 2^20.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1u8);
+    /// let mut bytes = Vec::new();
+    /// tree.write_to(&mut bytes, |item| vec![*item]).unwrap();
+    /// let read = QuadTree::<i32, u8>::read_from(&mut bytes.as_slice(), |bytes| Ok(bytes[0])).unwrap();
+    /// assert_eq!(read.iter().collect::<Vec<_>>(), vec![&1]);
+    /// ```
+    pub fn write_to<W>(
+        &self,
+        w: &mut W,
+        mut serialize: impl FnMut(&Item) -> Vec<u8>,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        persist::write_to(self, w, &mut serialize)
+    }
+
+    /// Check the structure of the tree.
+    ///
+    /// Every point must be inside the boundary of its node, quadrants must match the split of their parent
+    /// and nodes with quadrants must hold at least one item below them. Leaves must not hold more items than their capacity,
+    /// unless they can not be split any further. Lowering the capacity with `set_node_capacity` may leave leaves over capacity.
+    /// # Errors
+    /// Returns the first broken invariant found.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,2), 1);
+    /// tree.insert_at((8,8), 2);
+    /// tree.insert_at((8,2), 3);
+    /// assert!(tree.validate().is_ok());
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError<C>> {
+        validate::validate(self)
+    }
+
+    /// Returns the capacity
+    ///
+    /// This is the number of items a single node can hold, see [`QuadTree::node_capacity`].
+    pub fn capacity(&self) -> usize {
+        self.capacity.capacity()
+    }
+
+    /// Returns the number of items the root node can hold before it is split.
+    ///
+    /// There is no limit for the whole tree. Once a node is full, new items are stored in its quadrants.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// for i in 0..5 {
+    ///     tree.insert_at((i, i), i);
+    /// }
+    /// assert_eq!(tree.node_capacity(), 2);
+    /// assert_eq!(tree.len(), 5);
+    /// ```
+    pub fn node_capacity(&self) -> usize {
+        self.capacity.capacity()
+    }
+
+    /// Remove an item equal to `value` which is stored at most `tolerance` away from `point` along each axis.
+    /// This is useful for float coordinates which do not exactly match the stored ones.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0.,0.), (10.,10.)));
+    /// tree.insert_at((0.1 + 0.2, 5.), 1);
+    /// assert_eq!(tree.remove_near((0.3, 5.), 0.001, &2), None);
+    /// assert_eq!(tree.remove_near((0.3, 5.), 0.001, &1), Some(1));
+    /// assert_eq!(tree.iter().count(), 0);
+    /// ```
+    pub fn remove_near(
+        &mut self,
+        point: impl IntoPoint<C>,
+        tolerance: C,
+        value: &Item,
+    ) -> Option<Item>
+    where
+        Item: PartialEq,
+    {
+        self.generation.bump();
+        let point = point.into_point();
+        let tolerance = boundary::to_f64(tolerance);
+        self.remove_first_where(
+            &|b| Chebyshev::min_distance_to_boundary(&point, b) <= tolerance,
+            &mut |p, i| i == value && Chebyshev::distance(&point, p) <= tolerance,
+        )
+        .map(|(_, item)| item)
+    }
+
+    /// Checks if an item is stored exactly at `point`.
+    /// Only nodes containing `point` are visited. Coordinates are compared for exact equality, also for floats.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0.,0.), (10.,10.)));
+    /// tree.insert_at((0.1 + 0.2, 5.), 1);
+    /// assert!(tree.contains_point((0.1 + 0.2, 5.)));
+    /// assert!(!tree.contains_point((0.3, 5.)));
+    /// ```
+    pub fn contains_point(&self, point: impl IntoPoint<C>) -> bool {
+        self.items_at(point.into_point()).next().is_some()
+    }
+
+    /// Get all items stored exactly at `point`.
+    /// Only nodes containing `point` are visited. Coordinates are compared for exact equality, also for floats.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1);
+    /// tree.insert_at((3,5), 2);
+    /// tree.insert_at((5,3), 3);
+    /// let mut items = tree.get_at((3,5));
+    /// items.sort();
+    /// assert_eq!(items, vec![&1, &2]);
+    /// ```
+    pub fn get_at(&self, point: impl IntoPoint<C>) -> Vec<&Item> {
+        self.items_at(point.into_point()).collect()
+    }
+
+    /// Lazily yields all items stored exactly at `point`
+    fn items_at(&self, point: Point<C>) -> impl Iterator<Item = &Item> {
+        let mut stack = vec![self];
+        let mut items = [].iter();
+        std::iter::from_fn(move || loop {
+            if let Some((_, item)) = items.find(|(p, _)| *p == point) {
+                return Some(item);
+            }
+            let node = stack.pop()?;
+            items = node.items.as_deref().unwrap_or_default().iter();
+            stack.extend(
+                node.quadrants
+                    .iter()
+                    .flat_map(|q| q.iter())
+                    .filter(|q| q.boundary.contains(&point)),
+            );
+        })
+    }
+
+    /// Remove the first item stored exactly at `point` and return it.
+    /// Quadrants are collapsed once all of them are empty.
+    /// Returns `None` if no item is stored at `point`, including points outside of the tree.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1);
+    /// assert_eq!(tree.remove_at((5,3)), None);
+    /// assert_eq!(tree.remove_at((30,50)), None);
+    /// assert_eq!(tree.remove_at((3,5)), Some(1));
+    /// assert_eq!(tree.len(), 0);
+    /// ```
+    pub fn remove_at(&mut self, point: impl IntoPoint<C>) -> Option<Item> {
+        self.generation.bump();
+        let point = point.into_point();
+        if !self.boundary.contains(&point) {
+            return None;
+        }
+        self.remove_first_where(&|b| b.contains(&point), &mut |p, _| *p == point)
+            .map(|(_, item)| item)
+    }
+
+    /// Remove and return an item.
+    /// Items of a node are taken before items of its quadrants, starting with the most recently inserted one.
+    /// Quadrants are emptied one after another, top left to bottom right.
+    /// Each call only walks down to the first node holding items and collapses emptied nodes on the way back,
+    /// so the tree can be drained like a work queue.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 1);
+    /// tree.insert_at((2,2), 2);
+    /// tree.insert_at((8,8), 3);
+    /// tree.insert_at((3,3), 4);
+    /// let popped = std::iter::from_fn(|| tree.pop()).map(|(_, item)| item).collect::<Vec<_>>();
+    /// assert_eq!(popped, vec![1, 2, 4, 3]);
+    /// ```
+    pub fn pop(&mut self) -> Option<(Point<C>, Item)> {
+        self.generation.bump();
+        self.pop_depth_first()
+    }
+
+    /// Removes the last item of the first node holding items in depth first order and collapses emptied nodes on the way back
+    fn pop_depth_first(&mut self) -> Option<(Point<C>, Item)> {
+        if let Some(items) = self.items.as_mut() {
+            let item = items.pop();
+            if items.is_empty() {
+                self.items = None;
+            }
+            if item.is_some() {
+                return item;
+            }
+        }
+        let item = self
+            .quadrants
+            .as_mut()?
+            .iter_mut()
+            .find_map(|quad| quad.pop_depth_first());
+        self.collapse_empty_quadrants();
+        item
+    }
+
+    /// Turn the tree into a read only tree which can be shared between threads.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1);
+    /// let shared = tree.share();
+    /// let other = shared.clone();
+    /// assert_eq!(other.iter().collect::<Vec<_>>(), vec![&1]);
+    /// ```
+    pub fn share(self) -> SharedQuadTree<C, Item, Cap> {
+        SharedQuadTree::new(self)
+    }
+
+    /// Remove all items inside a boundary and return them as a new tree covering that boundary.
+    /// The new tree uses the same settings as this tree.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), "a");
+    /// tree.insert_at((4,4), "b");
+    /// tree.insert_at((8,8), "c");
+    /// let extracted = tree.extract_area(Boundary::between_points((0,0), (5,5)));
+    /// assert_eq!(extracted.boundary(), &Boundary::between_points((0,0), (5,5)));
+    /// assert_eq!(extracted.capacity(), 2);
+    /// let mut items = extracted.iter().collect::<Vec<_>>();
+    /// items.sort();
+    /// assert_eq!(items, vec![&"a", &"b"]);
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&"c"]);
+    /// ```
+    pub fn extract_area(&mut self, area: Boundary<C>) -> QuadTree<C, Item, Cap> {
+        self.generation.bump();
+        // The extracted tree is a new root, so it keeps the depth limit of this tree
+        let mut extracted = QuadTree {
+            max_depth: self.max_depth,
+            ..self.new_child(area, self.capacity.for_new_node())
+        };
+        self.remove_in_area(&area, &mut |point, item| {
+            extracted.insert_at_unchecked(point, item)
+        });
+        extracted
+    }
+
+    /// Remove all items and quadrants.
+    /// The boundary, capacity and settings of the tree are kept.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((2,2), 1);
+    /// tree.insert_at((8,8), 2);
+    /// tree.clear();
+    /// assert!(tree.is_empty());
+    /// assert_eq!(tree.depth(), 0);
+    /// assert_eq!(tree.boundary(), &Boundary::between_points((0,0), (10,10)));
+    /// ```
+    pub fn clear(&mut self) {
+        self.generation.bump();
+        self.items = None;
+        self.quadrants = None;
+    }
+
+    /// Remove all items inside an area without returning them.
+    /// Subtrees left without any items are collapsed.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), "wall");
+    /// tree.insert_at((4,2), "wall");
+    /// tree.insert_at((8,8), "door");
+    /// tree.clear_area(Boundary::between_points((0,0), (5,5)));
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&"door"]);
+    /// ```
+    pub fn clear_area<A>(&mut self, area: A)
+    where
+        A: Area<C>,
+    {
+        self.generation.bump();
+        self.clear_area_rec(&area);
+    }
+
+    fn clear_area_rec<A>(&mut self, area: &A)
+    where
+        A: Area<C>,
+    {
+        if !area.intersects(&self.boundary) {
+            return;
+        }
+        if boundary::area_encloses(area, &self.boundary) {
+            self.items = None;
+            self.quadrants = None;
+            return;
+        }
+        if let Some(items) = &mut self.items {
+            items.retain(|(point, _)| !area.contains(point));
+            if items.is_empty() {
+                self.items = None;
+            }
+        }
+        if let Some(quads) = &mut self.quadrants {
+            quads.iter_mut().for_each(|q| q.clear_area_rec(area));
+        }
+        self.collapse_empty_quadrants();
+    }
+
+    /// Call `f` for every item, allowing it to modify the item.
+    /// Items for which `f` returns `false` are removed. Subtrees left without any items are collapsed.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 1);
+    /// tree.insert_at((5,5), 3);
+    /// tree.insert_at((8,8), 2);
+    /// // Count down every timer and drop the expired ones
+    /// tree.drain_while(|_, ticks| {
+    ///     *ticks -= 1;
+    ///     *ticks > 0
+    /// });
+    /// let mut ticks = tree.iter().collect::<Vec<_>>();
+    /// ticks.sort();
+    /// assert_eq!(ticks, vec![&1, &2]);
+    /// ```
+    pub fn drain_while<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Point<C>, &mut Item) -> bool,
+    {
+        self.generation.bump();
+        self.drain_while_rec(&mut f);
+    }
+
+    /// Same as `drain_while` but returns the number of removed items.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((1,1), 1);
+    /// tree.insert_at((4,9), 2);
+    /// tree.insert_at((6,2), 3);
+    /// tree.insert_at((9,9), 4);
+    /// assert_eq!(tree.retain_count(|p, _| p.x < 5), 2);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn retain_count<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&Point<C>, &mut Item) -> bool,
+    {
+        self.generation.bump();
+        let mut removed = 0;
+        self.drain_while_rec(&mut |point: &Point<C>, item: &mut Item| {
+            let keep = f(point, item);
+            removed += usize::from(!keep);
+            keep
+        });
+        removed
+    }
+
+    fn drain_while_rec<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&Point<C>, &mut Item) -> bool,
+    {
+        if let Some(items) = &mut self.items {
+            items.retain_mut(|(point, item)| f(point, item));
+            if items.is_empty() {
+                self.items = None;
+            }
+        }
+        if let Some(quads) = &mut self.quadrants {
+            quads.iter_mut().for_each(|q| q.drain_while_rec(f));
+        }
+        self.collapse_empty_quadrants();
+    }
+
+    /// Removes all items inside `area` and passes them to `f`.
+    /// Subtrees left without any items are collapsed.
+    fn remove_in_area<A>(&mut self, area: &A, f: &mut impl FnMut(Point<C>, Item))
+    where
+        A: Area<C>,
+    {
+        if !area.intersects(&self.boundary) {
+            return;
+        }
+        if let Some(items) = self.items.take() {
+            let mut kept = Vec::with_capacity(items.len());
+            for (point, item) in items {
+                if area.contains(&point) {
+                    f(point, item);
+                } else {
+                    kept.push((point, item));
+                }
+            }
+            if !kept.is_empty() {
+                self.items = Some(kept);
+            }
+        }
+        if let Some(quads) = &mut self.quadrants {
+            quads.iter_mut().for_each(|q| q.remove_in_area(area, f));
+        }
+        self.collapse_empty_quadrants();
+    }
+
+    /// Removes the first item matching `matches` from all nodes accepted by `visit`.
+    /// Subtrees left without any items are collapsed.
+    fn remove_first_where(
+        &mut self,
+        visit: &impl Fn(&Boundary<C>) -> bool,
+        matches: &mut impl FnMut(&Point<C>, &Item) -> bool,
+    ) -> Option<(Point<C>, Item)> {
+        if let Some(items) = &mut self.items {
+            if let Some(index) = items.iter().position(|(p, i)| matches(p, i)) {
+                let removed = items.remove(index);
+                if items.is_empty() {
+                    self.items = None;
+                }
+                return Some(removed);
+            }
+        }
+        let removed = self
+            .quadrants
+            .iter_mut()
+            .flat_map(|q| q.iter_mut())
+            .filter(|q| visit(&q.boundary))
+            .find_map(|q| q.remove_first_where(visit, matches));
+        if removed.is_some() {
+            self.collapse_empty_quadrants();
+        }
+        removed
+    }
+
+    fn collapse_empty_quadrants(&mut self) {
+        let is_empty = |q: &QuadTree<C, Item, Cap>| {
+            q.items.as_deref().unwrap_or_default().is_empty() && q.quadrants.is_none()
+        };
+        if self
+            .quadrants
+            .as_ref()
+            .is_some_and(|quads| quads.iter().all(is_empty))
+        {
+            self.quadrants = None;
+        }
+    }
+}
+
+impl<C, Item, Cap> QuadTree<C, Item, Cap>
+where
+    C: Coordinate,
+{
+    /// Returns the number of levels below the root node
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// tree.insert_at((3,5), 1);
+    /// assert_eq!(tree.depth(), 0);
+    /// tree.insert_at((1,0), 2);
+    /// assert_eq!(tree.depth(), 1);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.quadrants
+            .iter()
+            .flat_map(|q| q.iter())
+            .map(|q| q.depth() + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of items in the tree
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<1>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// assert_eq!(tree.len(), 0);
+    /// tree.insert_at((3,5), 1);
+    /// tree.insert_at((1,0), 2);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.items.as_ref().map(|i| i.len()).unwrap_or_default()
+            + self
+                .quadrants
+                .iter()
+                .flat_map(|q| q.iter())
+                .map(|q| q.len())
+                .sum::<usize>()
+    }
+
+    /// Returns true if the tree does not contain any items
+    pub fn is_empty(&self) -> bool {
+        self.items.as_deref().unwrap_or_default().is_empty()
+            && self
+                .quadrants
+                .iter()
+                .flat_map(|q| q.iter())
+                .all(|q| q.is_empty())
+    }
+
+    fn node_count(&self) -> usize {
+        1 + self
+            .quadrants
+            .iter()
+            .flat_map(|q| q.iter())
+            .map(|q| q.node_count())
+            .sum::<usize>()
+    }
+}
+
+impl<C, Item, Cap> QuadTree<C, Item, Cap>
+where
+    Cap: ItemCapacity<Item>,
+    C: Coordinate,
+    Item: AsPoint<C>,
+{
+    /// Insert a new item
+    /// # Errors
+    /// Returns an error if the item is out of bounds.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// struct Item {
+    ///     x: usize,
+    ///     y: usize,
+    /// }
+    /// impl AsPoint<usize> for Item {
+    ///     fn as_point(&self) -> Point<usize> {
+    ///         (self.x, self.y).into()
+    ///     }
+    /// }
+    /// let mut quad_tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0),(10,10)), 5);
+    /// assert!(quad_tree.insert(Item {
+    ///     x: 5,
+    ///     y: 5,
+    /// }).is_ok());
+    /// ```
+    pub fn insert(&mut self, item: Item) -> Result<(), QuadTreeError<C>> {
+        self.generation.bump();
+        self.insert_at(item.as_point(), item)
+    }
+
+    /// Insert all items of an iterator at their own position.
+    /// Every point is checked, items which are out of bounds or invalid are not inserted and returned in order instead.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// #[derive(Debug, PartialEq)]
+    /// struct Item(usize, usize);
+    /// impl AsPoint<usize> for Item {
+    ///     fn as_point(&self) -> Point<usize> {
+    ///         (self.0, self.1).into()
+    ///     }
+    /// }
+    /// let mut tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0),(10,10)), 5);
+    /// let rejected = tree.try_extend([Item(1, 1), Item(11, 11), Item(5, 5)]);
+    /// assert_eq!(rejected, vec![Item(11, 11)]);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn try_extend(&mut self, iter: impl IntoIterator<Item = Item>) -> Vec<Item> {
+        self.generation.bump();
+        self.try_extend_at(iter.into_iter().map(|item| (item.as_point(), item)))
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect()
+    }
+
+    /// Same as `insert` except that no bounds check is performed.
+    pub fn insert_unchecked(&mut self, item: Item) {
+        self.generation.bump();
+        self.insert_at_unchecked(item.as_point(), item)
+    }
+
+    /// Insert an item, replacing an item with the same key at the same position.
+    /// Returns the replaced item.
+    /// # Errors
+    /// Returns an error if the item is out of bounds.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// struct Entity {
+    ///     id: u32,
+    ///     pos: (usize, usize),
+    ///     health: u8,
+    /// }
+    /// impl AsPoint<usize> for Entity {
+    ///     fn as_point(&self) -> Point<usize> {
+    ///         self.pos.into()
+    ///     }
+    /// }
+    /// impl Keyed for Entity {
+    ///     type Key = u32;
+    ///     fn key(&self) -> u32 {
+    ///         self.id
+    ///     }
+    /// }
+    /// let mut tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0),(10,10)), 5);
+    /// assert!(tree.insert_keyed(Entity { id: 1, pos: (5, 5), health: 10 }).unwrap().is_none());
+    /// let old = tree.insert_keyed(Entity { id: 1, pos: (5, 5), health: 7 }).unwrap();
+    /// assert_eq!(old.map(|e| e.health), Some(10));
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn insert_keyed(&mut self, item: Item) -> Result<Option<Item>, QuadTreeError<C>>
+    where
+        Item: Keyed,
+    {
+        self.generation.bump();
+        let point = item.as_point();
+        if !self.boundary.contains(&point) {
+            return Err(QuadTreeError::OutOfBounds(self.boundary, point));
+        }
+        let key = item.key();
+        let replaced = self
+            .remove_first_where(&|b| b.contains(&point), &mut |p, i| {
+                *p == point && i.key() == key
+            })
+            .map(|(_, item)| item);
+        self.insert_tracked_unchecked(point, item);
+        Ok(replaced)
+    }
+
+    /// Remove an item equal to `item` which is stored at its own position.
+    /// Quadrants are collapsed once all of them are empty.
+    /// Returns `true` if an item was removed.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// #[derive(PartialEq)]
+    /// struct Entity {
+    ///     id: u32,
+    ///     pos: (usize, usize),
+    /// }
+    /// impl AsPoint<usize> for Entity {
+    ///     fn as_point(&self) -> Point<usize> {
+    ///         self.pos.into()
+    ///     }
+    /// }
+    /// let mut tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0),(10,10)), 5);
+    /// assert!(tree.insert(Entity { id: 1, pos: (5, 5) }).is_ok());
+    /// assert!(!tree.remove_item(&Entity { id: 2, pos: (5, 5) }));
+    /// assert!(tree.remove_item(&Entity { id: 1, pos: (5, 5) }));
+    /// assert_eq!(tree.len(), 0);
+    /// ```
+    pub fn remove_item(&mut self, item: &Item) -> bool
+    where
+        Item: PartialEq,
+    {
+        self.generation.bump();
+        let point = item.as_point();
+        if !self.boundary.contains(&point) {
+            return false;
+        }
+        self.remove_first_where(&|b| b.contains(&point), &mut |p, i| {
+            *p == point && i == item
+        })
+        .is_some()
+    }
+}
+
+impl<C, Cap> QuadTree<C, usize, Cap>
+where
+    Cap: ItemCapacity<usize>,
+    C: Coordinate,
+{
+    /// Get all indices in a given area.
+    /// This is useful if the items are stored in a separate collection and the tree only holds their indices.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let positions = [(3,5), (1,0), (7,3)];
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// for (index, position) in positions.iter().enumerate() {
+    ///     tree.insert_at(*position, index);
+    /// }
+    /// let mut res = tree.query_indices(Boundary::between_points((2,1), (8,9))).collect::<Vec<_>>();
+    /// res.sort();
+    /// assert_eq!(res, vec![0, 2]);
+    /// ```
+    pub fn query_indices<'a, A>(&'a self, area: A) -> impl Iterator<Item = usize> + 'a
+    where
+        A: Area<C> + 'a,
+    {
+        self.query(area).copied()
+    }
+}
+
+impl<C, Item> QuadTree<C, Item, DynCap>
+where
+    C: Coordinate,
+{
+    /// Create a new QuadTree
+    /// A capacity of 0 is treated as 1.
+    pub fn new_with_dyn_cap(boundary: Boundary<C>, cap: usize) -> Self {
+        Self::new_with_capacity(boundary, DynCap::new(cap))
+    }
+
+    /// Change the capacity of all nodes.
+    /// A capacity of 0 is treated as 1.
+    ///
+    /// Existing nodes are not rebuilt. Nodes holding more items than the new capacity keep them,
+    /// only new items are passed on to their quadrants. No memory is reserved for the new capacity.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0), (10,10)), 1);
+    /// tree.insert_at((3,5), 1);
+    /// tree.set_node_capacity(2);
+    /// tree.insert_at((1,0), 2);
+    /// assert_eq!(tree.node_capacity(), 2);
+    /// assert_eq!(tree.depth(), 0);
+    /// ```
+    pub fn set_node_capacity(&mut self, new_cap: usize) {
+        self.generation.bump();
+        let capacity = DynCap::new(new_cap);
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            node.capacity = capacity;
+            stack.extend(node.quadrants.iter_mut().flat_map(|q| q.iter_mut()));
+        }
+    }
+
+    /// Read a tree written by [`QuadTree::write_to`].
+    /// `deserialize` receives the bytes of a single item.
+    /// # Errors
+    /// Returns an error if reading from `r` or `deserialize` fails, or if the data is not a valid tree.
+    /// A capacity of 0 or larger than 2^20 is rejected instead of being used to allocate nodes.
+    pub fn read_from<R>(
+        r: &mut R,
+        mut deserialize: impl FnMut(&[u8]) -> std::io::Result<Item>,
+    ) -> std::io::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        persist::read_from(r, &mut deserialize)
+    }
+}
+
+impl<C, Item, const CAP: usize> QuadTree<C, Item, ConstCap<CAP>>
+where
+    C: Coordinate,
+{
+    /// Create a new QuadTree with a constant capacity
+    pub fn new_with_const_cap(boundary: Boundary<C>) -> Self {
+        let capacity = ConstCap;
+        Self::new_with_capacity(boundary, capacity)
+    }
+}
+
+/// Number of levels encoded in ids returned by `QuadTree::insert_at_with_id`
+const MAX_ID_LEVELS: usize = 29;
+/// Largest depth which fits into the lower bits of an id
+const MAX_ID_DEPTH_BITS: usize = 0b11_1111;
+
+/// Index of the quadrant containing `point` given the boundary of the first (top left) quadrant
+/// and the number of quadrants of the node
+#[inline]
+fn quadrant_index<C>(first_quadrant: &Boundary<C>, quadrant_count: usize, point: &Point<C>) -> usize
+where
+    C: Coordinate,
+{
+    let is_in_right_half = (first_quadrant.p2.x < point.x) as usize;
+    let is_in_bottom_half = (first_quadrant.p2.y < point.y) as usize;
+    // A node split along a single axis has two halves, at most one of both flags can be set
+    is_in_bottom_half * (quadrant_count / 2) + is_in_right_half
+}
+
+impl<C> From<(C, C)> for Point<C>
+where
+    C: Coordinate,
+{
+    fn from((x, y): (C, C)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<C> From<[C; 2]> for Point<C>
+where
+    C: Coordinate,
+{
+    fn from([x, y]: [C; 2]) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<C> From<Point<C>> for (C, C)
+where
+    C: Coordinate,
+{
+    fn from(Point { x, y }: Point<C>) -> Self {
+        (x, y)
+    }
+}
+
+impl<C> From<Point<C>> for [C; 2]
+where
+    C: Coordinate,
+{
+    fn from(Point { x, y }: Point<C>) -> Self {
+        [x, y]
+    }
+}
+
+/// Points are ordered by x first and y second.
+/// Only available for coordinates implementing `Ord`, which excludes floats.
+impl<C> Ord for Point<C>
+where
+    C: Coordinate + Ord,
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.x.cmp(&other.x).then_with(|| self.y.cmp(&other.y))
+    }
+}
+
+impl<C> PartialOrd for Point<C>
+where
+    C: Coordinate + Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C, T> IntoPoint<C> for T
+where
+    C: Coordinate,
+    T: Into<Point<C>>,
+{
+    fn into_point(self) -> Point<C> {
+        self.into()
+    }
+}
+
+impl<C, T> FromPoint<C> for T
+where
+    C: Coordinate,
+    T: From<Point<C>>,
+{
+    fn from_point(point: Point<C>) -> Self {
+        point.into()
+    }
+}
+
+impl<C> Display for Point<C>
+where
+    C: Coordinate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({:?},{:?})", self.x, self.y)
+    }
+}
+
+/// Inserts all items whose points are inside the tree.
+/// Items which are out of bounds or invalid are skipped, use [`QuadTree::try_extend_at`] to get them back.
+/// # Example
+/// ```
+/// use qutee::*;
+/// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+/// tree.extend([((1,1), 1), ((11,11), 2), ((5,5), 3)]);
+/// assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![1, 3]);
+/// ```
+impl<C, P, Item, Cap> Extend<(P, Item)> for QuadTree<C, Item, Cap>
+where
+    C: Coordinate,
+    P: IntoPoint<C>,
+    Cap: ItemCapacity<Item>,
+{
+    fn extend<I: IntoIterator<Item = (P, Item)>>(&mut self, iter: I) {
+        self.try_extend_at(iter);
+    }
+}
+
+/// Prints a summary of the tree.
+/// The alternate form `{:#?}` prints all nodes and items.
+impl<C, Item, Cap> Debug for QuadTree<C, Item, Cap>
+where
+    C: Coordinate,
+    Item: Debug,
+    Cap: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            f.debug_struct("QuadTree")
+                .field("boundary", &self.boundary)
+                .field("quadrants", &self.quadrants)
+                .field("items", &self.items)
+                .field("capacity", &self.capacity)
+                .field("preallocate_items", &self.preallocate_items)
+                .field("adaptive_split", &self.adaptive_split)
+                .field("max_depth", &self.max_depth)
+                .finish()
+        } else {
+            f.debug_struct("QuadTree")
+                .field("boundary", &self.boundary)
+                .field("len", &self.len())
+                .field("depth", &self.depth())
+                .field("node_count", &self.node_count())
+                .finish()
+        }
+    }
+}
+
+impl<C> QuadTreeError<C>
+where
+    C: Coordinate,
+{
+    /// Returns the rejected point moved into `boundary`, see [`Boundary::clamp_point`].
+    /// This allows retrying an insert at the edge of the tree.
+    /// Returns `None` for points with invalid coordinates (e.g. NaN), which can not be clamped.
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let mut tree = QuadTree::<_,_,ConstCap<2>>::new_with_const_cap(Boundary::between_points((0,0), (10,10)));
+    /// let err = tree.insert_at((12,5), 1).unwrap_err();
+    /// let clamped = err.clamped_to(tree.boundary()).unwrap();
+    /// assert_eq!(clamped, Point::new(10,5));
+    /// assert!(tree.insert_at(clamped, 1).is_ok());
+    /// ```
+    pub fn clamped_to(&self, boundary: &Boundary<C>) -> Option<Point<C>> {
+        match self {
+            Self::OutOfBounds(_, point) if point.is_valid() => Some(boundary.clamp_point(point)),
+            Self::OutOfBounds(..) | Self::InvalidPoint(_) => None,
+        }
+    }
+}
+
+impl<C> Error for QuadTreeError<C> where C: Coordinate {}
+
+impl<C> Display for QuadTreeError<C>
+where
+    C: Coordinate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl<C> Debug for QuadTreeError<C>
+where
+    C: Coordinate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfBounds(boundary, point) => {
+                write!(f, "point {point} is outside of area {boundary}")
+            }
+            Self::InvalidPoint(point) => {
+                write!(f, "point {point} is not a valid coordinate")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        f64::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    };
+
+    use rand::{Rng, SeedableRng};
+    use test_case::test_case;
+
+    use crate::{
+        bounds::ConstCap, Area, AsPoint, Boundary, Coordinate, DynCap, Euclidean, Metric, Point,
+        QuadTree, QuadTreeError,
+    };
+
+    #[test]
+    fn node_capacity_is_not_total_capacity() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 3);
+        for i in 0..=100 {
+            assert!(tree.insert_at((i, 100 - i), i).is_ok());
+        }
+        assert_eq!(tree.node_capacity(), 3);
+        assert_eq!(tree.capacity(), tree.node_capacity());
+        assert_eq!(tree.len(), 101);
+        assert!(tree.len() > tree.node_capacity());
+    }
+
+    #[test]
+    fn new_dispatches_capacity() {
+        let boundary = Boundary::new((0, 0), 100, 100);
+        let mut dyn_tree = QuadTree::new(boundary, 3);
+        let mut const_tree = QuadTree::new(boundary, ConstCap::<3>);
+        assert_eq!(dyn_tree.capacity(), 3);
+        assert_eq!(const_tree.capacity(), 3);
+        assert_eq!(
+            QuadTree::<_, usize, _>::new(boundary, 0).capacity(),
+            DynCap::new(0).0
+        );
+        for i in 0..=100 {
+            assert!(dyn_tree.insert_at((i, 100 - i), i).is_ok());
+            assert!(const_tree.insert_at((i, 100 - i), i).is_ok());
+        }
+        assert_eq!(dyn_tree.depth(), const_tree.depth());
+        assert_eq!(dyn_tree.len(), const_tree.len());
+    }
+
+    #[test]
+    fn preallocated_items() {
+        let boundary = Boundary::new((0, 0), 1000, 1000);
+        let mut tree = QuadTree::new_with_dyn_cap(boundary, 8);
+        let mut preallocated =
+            QuadTree::new_with_dyn_cap(boundary, 8).with_preallocated_items(true);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(719);
+        for i in 0..2_000 {
+            let p = Point::new(rng.gen_range(0..=1000), rng.gen_range(0..=1000));
+            assert_eq!(
+                tree.insert_at_tracked(p, i),
+                preallocated.insert_at_tracked(p, i)
+            );
+        }
+        assert!(preallocated
+            .quadrants
+            .iter()
+            .flat_map(|q| q.iter())
+            .all(|q| q.items.as_ref().is_some_and(|i| i.capacity() >= 8)));
+        assert_eq!(
+            tree.iter_points().collect::<Vec<_>>(),
+            preallocated.iter_points().collect::<Vec<_>>()
+        );
+        assert_eq!(tree.depth(), preallocated.depth());
+        let area = Boundary::new((100, 200), 300, 400);
+        assert_eq!(
+            tree.query(area).collect::<Vec<_>>(),
+            preallocated.query(area).collect::<Vec<_>>()
+        );
+        tree.clear_area(area);
+        preallocated.clear_area(area);
+        tree.drain_while(|_, i| *i % 3 != 0);
+        preallocated.drain_while(|_, i| *i % 3 != 0);
+        assert_eq!(
+            tree.iter_points().collect::<Vec<_>>(),
+            preallocated.iter_points().collect::<Vec<_>>()
+        );
+        assert_eq!(tree.node_count(), preallocated.node_count());
+        preallocated.drain_while(|_, _| false);
+        assert!(preallocated.is_empty());
+        assert!(preallocated.quadrants.is_none());
+    }
+
+    #[test]
+    fn adaptive_split() {
+        let boundary = Boundary::new((0, 0), 1024, 32);
+        let mut tree = QuadTree::new_with_dyn_cap(boundary, 4);
+        let mut adaptive = QuadTree::new_with_dyn_cap(boundary, 4).with_adaptive_split(true);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(726);
+        let mut items = Vec::new();
+        for i in 0..1_000 {
+            let p = Point::new(rng.gen_range(0..=1024), rng.gen_range(0..=32));
+            assert!(tree.insert_at(p, i).is_ok());
+            assert!(adaptive.insert_at(p, i).is_ok());
+            items.push((p, i));
+        }
+        assert_consistent(&adaptive);
+        // Elongated nodes are halved until they are roughly square, then split into four quadrants
+        let mut counts = HashSet::new();
+        let mut stack = vec![&adaptive];
+        while let Some(node) = stack.pop() {
+            if let Some(quads) = &node.quadrants {
+                counts.insert(quads.len());
+                stack.extend(quads.iter());
+            }
+        }
+        assert_eq!(counts, HashSet::from([2, 4]));
+        assert_eq!(adaptive.quadrants.as_ref().map(|q| q.len()), Some(2));
+        // The leaves of the regular tree keep the aspect ratio of the root
+        let max_leaf_aspect_ratio = |tree: &QuadTree<i32, i32>| {
+            let mut max = 0.;
+            let mut stack = vec![tree];
+            while let Some(node) = stack.pop() {
+                match &node.quadrants {
+                    Some(quads) => stack.extend(quads.iter()),
+                    None => {
+                        let width = f64::from(node.boundary.p2.x - node.boundary.p1.x);
+                        let height = f64::from(node.boundary.p2.y - node.boundary.p1.y);
+                        max = f64::max(max, width.max(height) / width.min(height));
+                    }
+                }
+            }
+            max
+        };
+        assert_eq!(max_leaf_aspect_ratio(&tree), 32.);
+        assert!(max_leaf_aspect_ratio(&adaptive) <= 2.);
+
+        let mut check_queries = |adaptive: &QuadTree<i32, i32>| {
+            for _ in 0..50 {
+                let area = Boundary::between_points(
+                    (rng.gen_range(-2..=1026), rng.gen_range(-2..=34)),
+                    (rng.gen_range(-2..=1026), rng.gen_range(-2..=34)),
+                );
+                let mut expected = items
+                    .iter()
+                    .filter(|(p, _)| area.contains(p))
+                    .map(|(_, id)| *id)
+                    .collect::<Vec<_>>();
+                let mut res = adaptive.query(area).copied().collect::<Vec<_>>();
+                res.sort();
+                expected.sort();
+                assert_eq!(res, expected, "area {area}");
+            }
+        };
+        check_queries(&adaptive);
+        adaptive.rebalance();
+        assert_consistent(&adaptive);
+        check_queries(&adaptive);
+        assert_eq!(adaptive.iter_with_path().count(), items.len());
+    }
+
+    #[test]
+    fn set_node_capacity() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        assert!(tree.insert_at((10, 10), 0).is_ok());
+        assert!(tree.insert_at((90, 90), 1).is_ok());
+        tree.set_node_capacity(4);
+        assert!(tree.insert_at((10, 90), 2).is_ok());
+        assert!(tree.insert_at((90, 10), 3).is_ok());
+        assert_eq!(tree.depth(), 0);
+        assert!(tree.insert_at((50, 50), 4).is_ok());
+        assert_eq!(tree.depth(), 1);
+        assert!(tree
+            .quadrants
+            .iter()
+            .flat_map(|q| q.iter())
+            .all(|q| q.capacity() == 4));
+
+        tree.set_node_capacity(1);
+        assert_eq!(tree.items.as_ref().map(Vec::len), Some(4));
+        assert!(tree.insert_at((20, 20), 5).is_ok());
+        assert!(tree.insert_at((21, 21), 6).is_ok());
+        assert_eq!(tree.items.as_ref().map(Vec::len), Some(4));
+        assert!(tree.depth() >= 2);
+        assert_eq!(tree.len(), 7);
+        assert_consistent(&tree);
+
+        // Changing the limit does not allocate for it
+        tree.set_node_capacity(usize::MAX);
+        assert_eq!(tree.node_capacity(), usize::MAX);
+        assert_eq!(tree.len(), 7);
+    }
+
+    #[test]
+    fn insert_keyed() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Record {
+            id: u32,
+            pos: Point<i32>,
+            payload: &'static str,
+        }
+        impl crate::AsPoint<i32> for Record {
+            fn as_point(&self) -> Point<i32> {
+                self.pos
+            }
+        }
+        impl crate::Keyed for Record {
+            type Key = u32;
+            fn key(&self) -> u32 {
+                self.id
+            }
+        }
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        let record = |id, x, y, payload| Record {
+            id,
+            pos: Point::new(x, y),
+            payload,
+        };
+        for r in [
+            record(1, 3, 3, "a"),
+            record(2, 3, 3, "b"),
+            record(3, 8, 8, "c"),
+            record(1, 8, 8, "d"),
+        ] {
+            assert_eq!(tree.insert_keyed(r), Ok(None));
+        }
+        let old = tree.insert_keyed(record(1, 3, 3, "updated"));
+        assert_eq!(old, Ok(Some(record(1, 3, 3, "a"))));
+        assert_eq!(tree.len(), 4);
+        let at_position = tree
+            .iter()
+            .filter(|r| r.pos == Point::new(3, 3))
+            .map(|r| (r.id, r.payload))
+            .collect::<Vec<_>>();
+        assert_eq!(at_position.len(), 2);
+        assert!(at_position.contains(&(1, "updated")));
+        assert!(at_position.contains(&(2, "b")));
+        assert!(tree.insert_keyed(record(4, 11, 3, "out")).is_err());
+    }
+
+    #[test]
+    fn debug_summary() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2)
+            .with_adaptive_split(true)
+            .with_max_depth(5);
+        for i in 0..=10 {
+            assert!(tree.insert_at((i * 7, i * 9), i).is_ok());
+        }
+        let summary = format!("{tree:?}");
+        assert!(summary.contains("len: 11"), "{summary}");
+        assert!(summary.contains(&format!("depth: {}", tree.depth())));
+        assert!(!summary.contains("(70,90)"), "{summary}");
+        let full = format!("{tree:#?}");
+        assert!(full.contains("items"));
+        assert!(full.contains("70,\n"), "{full}");
+        assert!(full.contains("preallocate_items: false"), "{full}");
+        assert!(full.contains("adaptive_split: true"), "{full}");
+        assert!(full.contains("max_depth: 5"), "{full}");
+    }
+
+    #[test]
+    fn create_quad_tree() {
+        let boundary = Boundary::new((0, 0), 10, 10);
+        let tree = QuadTree::<usize, u8, ConstCap<20>>::new_with_const_cap(boundary);
+        assert_eq!(
+            QuadTree {
+                boundary,
+                quadrants: None,
+                items: None,
+                capacity: ConstCap,
+                preallocate_items: false,
+                adaptive_split: false,
+                max_depth: usize::MAX,
+                generation: Default::default(),
+            },
+            tree
+        );
+        assert_eq!(None, tree.items)
+    }
+
+    #[test]
+    fn insert_single() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 10);
+        assert!(tree.insert_at((10, 10), 1u8).is_ok());
+        assert_eq!(tree.items.unwrap()[0], ((10, 10).into(), 1));
+    }
+
+    #[test]
+    fn insert_out_of_bounds() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 10);
+        assert_eq!(
+            tree.insert_at((20, 20), 1u8),
+            Err(QuadTreeError::OutOfBounds(
+                Boundary::new((0, 0), 10, 10),
+                (20, 20).into()
+            ))
+        );
+    }
+
+    #[test]
+    fn insert_more_than_capacity() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        assert!(tree.quadrants.is_none());
+
+        assert!(tree.insert_at((1, 1), 1).is_ok());
+        assert!(tree.quadrants.is_none());
+        assert_eq!(tree.items.as_ref().unwrap().len(), 1);
+
+        assert!(tree.insert_at((2, 2), 1).is_ok());
+        assert_eq!(tree.items.as_ref().unwrap().len(), 1);
+        assert!(tree.quadrants.is_some());
+        let quads = tree.quadrants.as_ref().unwrap();
+        assert_eq!(quads[0].items.as_ref().unwrap().len(), 1);
+        assert_eq!(quads[1].items, None);
+        assert_eq!(quads[2].items, None);
+        assert_eq!(quads[3].items, None);
+
+        assert!(tree.insert_at((7, 7), 1).is_ok());
+        assert!(tree.quadrants.is_some());
+        let quads = tree.quadrants.as_ref().unwrap();
+        assert_eq!(quads[0].items.as_ref().unwrap().len(), 1);
+        assert_eq!(quads[1].items, None);
+        assert_eq!(quads[2].items, None);
+        assert_eq!(quads[3].items.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn insert_batch_checked_rejects_whole_batch() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        let batch = [
+            ((1, 1).into(), 1u8),
+            ((2, 2).into(), 2),
+            ((20, 2).into(), 3),
+            ((3, 3).into(), 4),
+        ];
+        assert_eq!(
+            tree.insert_batch_checked(&batch),
+            Err(QuadTreeError::OutOfBounds(
+                Boundary::new((0, 0), 10, 10),
+                (20, 2).into()
+            ))
+        );
+        assert!(tree.items.is_none());
+        assert!(tree.quadrants.is_none());
+    }
+
+    #[test]
+    fn try_extend_returns_rejects() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 100., 100.), 4);
+        let mut expected = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 100., 100.), 4);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(765);
+        let mut items = (0..500)
+            .map(|i| {
+                (
+                    Point::new(rng.gen_range(-10. ..110.), rng.gen_range(-10. ..110.)),
+                    i,
+                )
+            })
+            .collect::<Vec<_>>();
+        items.push((Point::new(f64::NAN, 5.), 500));
+        let rejected = tree.try_extend_at(items.clone());
+        let expected_rejects = items
+            .into_iter()
+            .filter(|(p, i)| expected.insert_at(*p, *i).is_err() || p.x.is_nan())
+            .map(|(_, i)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rejected.iter().map(|(_, i)| *i).collect::<Vec<_>>(),
+            expected_rejects
+        );
+        assert_eq!(expected_rejects.last(), Some(&500));
+        assert_eq!(tree.len() + rejected.len(), 501);
+        assert_consistent(&tree);
+    }
+
+    #[test]
+    fn extend_with_and_without_checks() {
+        let items = [((1, 1), 1), ((11, 11), 2), ((5, 5), 3), ((0, 12), 4)];
+        let mut checked = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        checked.extend(items);
+        let mut values = checked.iter().copied().collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, vec![1, 3]);
+        assert_consistent(&checked);
+
+        let mut unchecked = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        unchecked.extend_at(items);
+        assert_eq!(unchecked.len(), 4);
+    }
+
+    #[test]
+    fn bulk_load() {
+        let boundary = Boundary::new((0, 0), 1000, 1000);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(766);
+        let mut items = (0..2_000)
+            .map(|i| {
+                let p = Point::new(rng.gen_range(0..=1000), rng.gen_range(0..=1000));
+                (p, i)
+            })
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(p, _)| (p.x, p.y));
+        let mut inserted = QuadTree::new_with_dyn_cap(boundary, 8);
+        for (point, item) in &items {
+            assert!(inserted.insert_at(*point, *item).is_ok());
+        }
+        let loaded = QuadTree::bulk_load(boundary, DynCap::new(8), items.clone()).unwrap();
+        assert_consistent(&loaded);
+        assert!(loaded.depth() <= inserted.depth());
+        assert!(loaded.node_count() <= inserted.node_count());
+        let area = Boundary::new((100, 300), 400, 250);
+        let mut expected = inserted.query(area).collect::<Vec<_>>();
+        let mut actual = loaded.query(area).collect::<Vec<_>>();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+
+        items.push(((1001, 0).into(), 2_000));
+        assert_eq!(
+            QuadTree::bulk_load(boundary, DynCap::new(8), items),
+            Err(QuadTreeError::OutOfBounds(boundary, (1001, 0).into()))
+        );
+    }
+
+    #[test]
+    fn contains_point() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        assert!(!tree.contains_point((3, 5)));
+        assert!(tree.insert_at((3, 5), 1).is_ok());
+        assert!(tree.contains_point((3, 5)));
+        assert!(!tree.contains_point((3, 6)));
+        assert!(!tree.contains_point((30, 5)));
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(767);
+        let points = (0..200)
+            .map(|_| Point::new(rng.gen_range(0..=10), rng.gen_range(0..=10)))
+            .collect::<HashSet<_>>();
+        for (i, point) in points.iter().enumerate() {
+            assert!(tree.insert_at(*point, i).is_ok());
+        }
+        for x in -1..=11 {
+            for y in -1..=11 {
+                let point = Point::new(x, y);
+                assert_eq!(
+                    tree.contains_point(point),
+                    point == Point::new(3, 5) || points.contains(&point),
+                    "{point}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn insert_unchecked_out_of_bounds() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        tree.insert_at_unchecked((5, 5), 1);
+        tree.insert_at_unchecked((20, -5), 2);
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn rebalance_keeps_max_depth() {
+        let mut tree =
+            QuadTree::<_, _, ConstCap<4>>::new_with_const_cap(Boundary::new((0., 0.), 10., 10.))
+                .with_max_depth(3);
+        for i in 0..100 {
+            assert!(tree.insert_at((5., 5.), i).is_ok());
+        }
+        assert_eq!(tree.depth(), 3);
+        for _ in 0..3 {
+            tree.rebalance();
+        }
+        for i in 100..200 {
+            assert!(tree.insert_at((5., 5.), i).is_ok());
+        }
+        assert_eq!(tree.depth(), 3);
+        assert_eq!(tree.len(), 200);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn get_at_duplicates() {
+        let mut tree =
+            QuadTree::<_, _, ConstCap<4>>::new_with_const_cap(Boundary::new((0., 0.), 10., 10.))
+                .with_max_depth(3);
+        for i in 0..100 {
+            assert!(tree.insert_at((5., 5.), i).is_ok());
+            assert!(tree.insert_at((2.5, 7.5), i + 100).is_ok());
+        }
+        assert_eq!(tree.depth(), 3);
+        let mut items = tree
+            .get_at((5., 5.))
+            .into_iter()
+            .copied()
+            .collect::<Vec<_>>();
+        items.sort();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+        assert_eq!(tree.get_at((2.5, 7.5)).len(), 100);
+        assert!(tree.get_at((5., 5.000_001)).is_empty());
+        assert!(tree.get_at((50., 5.)).is_empty());
+    }
+
+    #[test]
+    fn query_filter() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        enum Team {
+            Red,
+            Blue,
+        }
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 4);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(769);
+        let mut inserted = Vec::new();
+        for i in 0..500 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            let team = if rng.gen_bool(0.3) {
+                Team::Red
+            } else {
+                Team::Blue
+            };
+            assert!(tree.insert_at(p, (i, team)).is_ok());
+            inserted.push((p, i, team));
+        }
+        let area = Boundary::new((20, 10), 50, 60);
+        let enemies = tree
+            .query_filter(area, |(_, team)| *team == Team::Red)
+            .map(|(i, _)| *i)
+            .collect::<HashSet<_>>();
+        let in_area = |p: &Point<i32>| (20..=70).contains(&p.x) && (10..=70).contains(&p.y);
+        let expected = inserted
+            .iter()
+            .filter(|(p, _, team)| in_area(p) && *team == Team::Red)
+            .map(|(_, i, _)| *i)
+            .collect::<HashSet<_>>();
+        assert!(!expected.is_empty());
+        assert!(expected.len() < inserted.iter().filter(|(p, ..)| in_area(p)).count());
+        assert_eq!(enemies, expected);
+    }
+
+    fn assert_consistent<C: Coordinate, Item>(tree: &QuadTree<C, Item, DynCap>) {
+        assert_eq!(tree.validate(), Ok(()));
+        for (point, _) in tree.items.iter().flatten() {
+            assert!(
+                tree.boundary.contains(point),
+                "{point} outside {}",
+                tree.boundary
+            );
+        }
+        for quad in tree.quadrants.iter().flat_map(|q| q.iter()) {
+            assert!(tree.boundary.encloses(&quad.boundary));
+            assert_consistent(quad);
+        }
+    }
+
+    #[test]
+    fn pop() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(730);
+        let mut inserted = Vec::new();
+        for i in 0..200 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
+            inserted.push((p, i));
+        }
+        let paths = tree
+            .iter_with_path()
+            .map(|(path, i)| (*i, path))
+            .collect::<HashMap<_, _>>();
+        let mut popped = Vec::new();
+        while let Some(item) = tree.pop() {
+            popped.push(item);
+            assert_consistent(&tree);
+        }
+        assert!(tree.is_empty());
+        assert!(tree.quadrants.is_none());
+        let popped_paths = popped.iter().map(|(_, i)| &paths[i]).collect::<Vec<_>>();
+        // A node is emptied before any of its quadrants
+        for (i, path) in popped_paths.iter().enumerate() {
+            assert!(popped_paths[i + 1..]
+                .iter()
+                .all(|later| later.len() >= path.len() || !path.starts_with(later)));
+        }
+        assert!(popped_paths[0].is_empty());
+        assert!(!popped_paths.last().unwrap().is_empty());
+        popped.sort_by_key(|(_, i)| *i);
+        assert_eq!(popped, inserted);
+    }
+
+    #[test]
+    fn insert_at_reduce() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        for i in 1..=10 {
+            assert!(tree.insert_at_reduce((5, 5), i, |sum, v| *sum += v).is_ok());
+            assert!(tree
+                .insert_at_reduce((i - 1, 2), 1, |sum, v| *sum += v)
+                .is_ok());
+        }
+        assert!(tree.insert_at_reduce((11, 5), 1, |_, _| {}).is_err());
+        assert_eq!(tree.len(), 11);
+        assert_eq!(
+            tree.query(Boundary::new((5, 5), 0, 0)).collect::<Vec<_>>(),
+            vec![&55]
+        );
+        assert!(tree
+            .query(Boundary::new((0, 2), 10, 0))
+            .all(|count| *count == 1));
+        assert_consistent(&tree);
+    }
+
+    #[test]
+    fn from_points() {
+        let boundary = Boundary::new((0, 0), 100, 100);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(736);
+        let mut points = (0..200)
+            .map(|i| {
+                (
+                    Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100)),
+                    i,
+                )
+            })
+            .collect::<Vec<_>>();
+        let tree = QuadTree::from_points(boundary, DynCap::new(3), points.clone()).unwrap();
+        let mut inserted = QuadTree::new_with_dyn_cap(boundary, 3);
+        for (p, i) in &points {
+            assert!(inserted.insert_at(*p, *i).is_ok());
+        }
+        assert_eq!(
+            tree.iter_points().collect::<Vec<_>>(),
+            inserted.iter_points().collect::<Vec<_>>()
+        );
+        assert_consistent(&tree);
+
+        points.insert(100, (Point::new(50, 101), 200));
+        assert_eq!(
+            QuadTree::from_points(boundary, DynCap::new(3), points).unwrap_err(),
+            QuadTreeError::OutOfBounds(boundary, Point::new(50, 101))
+        );
+    }
+
+    #[test]
+    fn retain_count() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(741);
+        for i in 0..300 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        let failing = tree.iter_points().filter(|(p, _)| p.x < 30).count();
+        assert_eq!(tree.retain_count(|p, _| p.x >= 30), failing);
+        assert_eq!(tree.len(), 300 - failing);
+        assert_eq!(tree.retain_count(|p, _| p.x >= 30), 0);
+        assert_consistent(&tree);
+        assert_eq!(tree.retain_count(|_, _| false), 300 - failing);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn remove_near() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 10., 10.), 1);
+        for i in 0..10 {
+            let v = i as f64;
+            assert!(tree.insert_at((v * 0.1 * 3., 5. + v * 0.1), i).is_ok());
+        }
+        // Stored coordinates are slightly off due to rounding
+        assert_eq!(tree.remove_near((0.9, 5.3), 1e-9, &3), Some(3));
+        assert_eq!(tree.remove_near((0.9, 5.3), 1e-9, &3), None);
+        // Item is within tolerance but on the other side of a split line
+        assert_eq!(tree.remove_near((5.0, 5.), 2.4, &9), Some(9));
+        assert_eq!(tree.iter().count(), 8);
+        assert_consistent(&tree);
+    }
+
+    #[test]
+    fn extract_area() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 16, 16), 2);
+        for x in 0..16 {
+            for y in 0..16 {
+                assert!(tree.insert_at((x, y), (x, y)).is_ok());
+            }
+        }
+        let quadrant = Boundary::new((0, 0), 7, 7);
+        let extracted = tree.extract_area(quadrant);
+        assert_eq!(extracted.boundary, quadrant);
+        assert_eq!(extracted.iter().count(), 64);
+        assert!(extracted.iter().all(|(x, y)| *x < 8 && *y < 8));
+        assert_eq!(tree.iter().count(), 256 - 64);
+        assert!(tree.iter().all(|(x, y)| *x >= 8 || *y >= 8));
+        assert_eq!(tree.query(quadrant).count(), 0);
+        assert_consistent(&tree);
+        assert_consistent(&extracted);
+
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 10., 10.), 4)
+            .with_max_depth(3)
+            .with_adaptive_split(true)
+            .with_preallocated_items(true);
+        for i in 0..100 {
+            assert!(tree.insert_at((2., 2.), i).is_ok());
+        }
+        let mut extracted = tree.extract_area(Boundary::new((0., 0.), 20., 5.));
+        assert!(tree.is_empty());
+        assert_eq!(extracted.len(), 100);
+        assert!(extracted.adaptive_split);
+        assert!(extracted.preallocate_items);
+        assert_eq!(extracted.max_depth, 3);
+        assert_eq!(extracted.depth(), 3);
+        assert!(extracted.insert_at((2., 2.), 100).is_ok());
+        assert_eq!(extracted.depth(), 3);
+        assert_consistent(&extracted);
+    }
+
+    #[test]
+    fn point_tuple_round_trip() {
+        let p = Point::from((3, -4));
+        let t: (i32, i32) = p.into();
+        assert_eq!(t, (3, -4));
+        assert_eq!(Point::from(t), p);
+        let a: [i32; 2] = p.into();
+        assert_eq!(a, [3, -4]);
+        assert_eq!(Point::from(a), p);
+    }
+
+    #[test_case(0., Point::new(2, -4); "start")]
+    #[test_case(0.5, Point::new(5, 1); "middle")]
+    #[test_case(1., Point::new(8, 6); "end")]
+    fn lerp_int(t: f64, expected: Point<i32>) {
+        assert_eq!(Point::new(2, -4).lerp(&Point::new(8, 6), t), expected);
+    }
+
+    #[test_case(0., Point::new(1., 2.); "start")]
+    #[test_case(0.5, Point::new(1.5, 3.25); "middle")]
+    #[test_case(1., Point::new(2., 4.5); "end")]
+    fn lerp_float(t: f64, expected: Point<f64>) {
+        assert_eq!(Point::new(1., 2.).lerp(&Point::new(2., 4.5), t), expected);
+    }
+
+    #[test]
+    fn lerp_truncates() {
+        assert_eq!(
+            Point::new(0, 0).lerp(&Point::new(3, -3), 0.5),
+            Point::new(1, -1)
+        );
+    }
+
+    #[test_case(FRAC_PI_2, (0, 0) => Point::new(-4, 3); "90 degrees around origin")]
+    #[test_case(PI, (0, 0) => Point::new(-3, -4); "180 degrees around origin")]
+    #[test_case(FRAC_PI_2, (1, 2) => Point::new(-1, 4); "90 degrees around pivot")]
+    #[test_case(PI, (1, 2) => Point::new(-1, 0); "180 degrees around pivot")]
+    #[test_case(-FRAC_PI_2, (1, 2) => Point::new(3, 0); "negative angle")]
+    fn rotate_around_int(angle: f64, pivot: (i32, i32)) -> Point<i32> {
+        Point::new(3, 4).rotate_around(&pivot.into(), angle)
+    }
+
+    #[test]
+    fn rotate_around_float() {
+        let p = Point::<f64>::new(3., 4.).rotate_around(&Point::new(1., 2.), FRAC_PI_2);
+        assert!((p.x + 1.).abs() < 1e-12 && (p.y - 4.).abs() < 1e-12, "{p}");
+        let p = Point::<f64>::new(3., 4.).rotate_around(&Point::new(0., 0.), PI);
+        assert!((p.x + 3.).abs() < 1e-12 && (p.y + 4.).abs() < 1e-12, "{p}");
+        // Not a multiple of 90 degrees, integer coordinates are rounded
+        assert_eq!(
+            Point::new(10, 0).rotate_around(&Point::new(0, 0), FRAC_PI_4),
+            Point::new(7, 7)
+        );
+    }
+
+    #[test]
+    fn insert_presorted_unchecked() {
+        let boundary = Boundary::new((0, 0), 100, 100);
+        let first_quadrant = boundary.split().unwrap()[0];
+        let mut expected = QuadTree::new_with_dyn_cap(boundary, 3);
+        let mut tree = QuadTree::new_with_dyn_cap(boundary, 3);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(21);
+        for i in 0..200 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            expected.insert_at_unchecked(p, i);
+            let hint = super::quadrant_index(&first_quadrant, 4, &p);
+            tree.insert_presorted_unchecked(p, i, Some(hint));
+        }
+        assert_eq!(
+            tree.iter_points().collect::<Vec<_>>(),
+            expected.iter_points().collect::<Vec<_>>()
+        );
+        assert_eq!(tree.depth(), expected.depth());
+        assert_consistent(&tree);
+    }
+
+    #[test]
+    fn drain_while() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 3);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        for _ in 0..300 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, rng.gen_range(1..=5)).is_ok());
+        }
+        for tick in 1..=5 {
+            let mut removed = 0;
+            tree.drain_while(|_, counter| {
+                *counter -= 1;
+                if *counter == 0 {
+                    removed += 1;
+                }
+                *counter > 0
+            });
+            assert!(tree.iter().all(|counter| *counter >= 1));
+            assert!(tree.iter().all(|counter| *counter <= 5 - tick));
+            assert_consistent(&tree);
+            if tick == 5 {
+                assert!(tree.is_empty());
+                assert!(tree.quadrants.is_none());
+            }
+            assert!(removed > 0);
+        }
+    }
+
+    #[test]
+    fn clear_area() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 16, 16), 2);
+        for x in 0..=16 {
+            for y in 0..=16 {
+                assert!(tree.insert_at((x, y), (x, y)).is_ok());
+            }
+        }
+        let center = Boundary::new((4, 4), 8, 8);
+        tree.clear_area(center);
+        assert_eq!(tree.iter().count(), 17 * 17 - 9 * 9);
+        assert!(tree.iter().all(|&(x, y)| !center.contains_xy(x, y)));
+        assert_eq!(tree.query(center).count(), 0);
+        assert_consistent(&tree);
+        tree.clear_area(Boundary::new((0, 0), 16, 16));
+        assert!(tree.is_empty());
+        assert!(tree.quadrants.is_none());
+    }
+
+    #[test]
+    fn insert_at_tracked() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        for p in [(1, 1), (2, 2)] {
+            let outcome = tree.insert_at_tracked(p, ()).unwrap();
+            assert!(!outcome.split_created);
+            assert_eq!(outcome.final_depth, 0);
+        }
+        let outcome = tree.insert_at_tracked((3, 3), ()).unwrap();
+        assert!(outcome.split_created);
+        assert_eq!(outcome.final_depth, 1);
+        let outcome = tree.insert_at_tracked((4, 4), ()).unwrap();
+        assert!(!outcome.split_created);
+        assert_eq!(outcome.final_depth, 1);
+        assert!(tree.insert_at_tracked((20, 20), ()).is_err());
+    }
+
+    #[test]
+    fn from_sorted_unchecked_matches_insert() {
+        fn morton(p: &Point<u32>) -> u64 {
+            (0..16).fold(0, |key, bit| {
+                key | ((p.x as u64 >> bit) & 1) << (2 * bit)
+                    | ((p.y as u64 >> bit) & 1) << (2 * bit + 1)
+            })
+        }
+        let boundary = Boundary::between_points((0, 0), (65_535, 65_535));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut items = (0..2_000)
+            .map(|i| {
+                let p = Point::new(rng.gen_range(0..65_536), rng.gen_range(0..65_536));
+                (p, i)
+            })
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(p, _)| morton(p));
+
+        let mut inserted = QuadTree::new_with_dyn_cap(boundary, 4);
+        for (p, i) in &items {
+            inserted.insert_at_unchecked(*p, *i);
+        }
+        let sorted = QuadTree::from_sorted_unchecked(boundary, DynCap::new(4), items);
+        for _ in 0..50 {
+            let area = Boundary::between_points(
+                (rng.gen_range(0..65_536), rng.gen_range(0..65_536)),
+                (rng.gen_range(0..65_536), rng.gen_range(0..65_536)),
+            );
+            let mut a = sorted.query(area).collect::<Vec<_>>();
+            let mut b = inserted.query(area).collect::<Vec<_>>();
+            a.sort();
+            b.sort();
+            assert_eq!(a, b);
+        }
+        assert_eq!(sorted.iter().count(), 2_000);
+    }
+
+    #[test]
+    fn insert_at_with_id() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 16, 16), 2);
+        for p in [(1, 1), (15, 15)] {
+            assert_eq!(tree.insert_at_with_id(p, ()), Ok(0));
+        }
+        let a = tree.insert_at_with_id((12, 12), ()).unwrap();
+        let b = tree.insert_at_with_id((13, 12), ()).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a >> 62, 3);
+        assert_eq!(a & 0b11_1111, 1);
+        let c = tree.insert_at_with_id((2, 3), ()).unwrap();
+        assert_eq!(c >> 62, 0);
+        assert_ne!(a, c);
+        assert!(tree.insert_at_with_id((17, 0), ()).is_err());
+    }
+
+    #[test]
+    fn insert_duplicates_into_unsplittable_node() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 1., 1.), 1);
+        for i in 0..100 {
+            assert!(tree.insert_at((0.3, 0.7), i).is_ok());
+        }
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.query(Boundary::new((0.3, 0.7), 0., 0.)).count(), 100);
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 1, 1), 1);
+        for p in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            assert_eq!(
+                tree.insert_at_tracked(p, ()),
+                Ok(crate::InsertOutcome::default())
+            );
+        }
+        assert!(tree.quadrants.is_none());
+    }
+
+    #[test]
+    fn rebalance_skewed() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 1024, 1024), 4);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(17);
+        // Sparse points first, a dense cluster last.
+        // The sparse points take the room along the path to the cluster, which pushes it down.
+        for _ in 0..100 {
+            let p = Point::new(rng.gen_range(0..=1024), rng.gen_range(0..=1024));
+            assert!(tree.insert_at(p, p).is_ok());
+        }
+        for x in 0..4 {
+            for y in 0..4 {
+                let p = Point::new(x * 8, y * 8);
+                assert!(tree.insert_at(p, p).is_ok());
+            }
+        }
+        let depth = tree.depth();
+        let mut before = tree.iter().copied().collect::<Vec<_>>();
+        tree.rebalance();
+        assert!(tree.depth() < depth, "{} >= {depth}", tree.depth());
+        let mut after = tree.iter().copied().collect::<Vec<_>>();
+        before.sort();
+        after.sort();
+        assert_eq!(before, after);
+        assert_consistent(&tree);
+        let area = Boundary::new((2, 2), 500, 3);
+        assert!(tree.query(area).all(|p| area.contains(p)));
+    }
+
+    #[test]
+    fn query() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((-10, -10), 20, 20), 2);
+        let mut expected = Vec::new();
+        for i in 1..10 {
+            assert!(tree.insert_at((i, i), i).is_ok());
+            assert!(tree.insert_at((-i, i), 0b1000_0000 | i).is_ok());
+            assert!(tree.insert_at((i, -i), 0b0100_0000 | i).is_ok());
+            assert!(tree.insert_at((-i, -i), 0b1100_0000 | i).is_ok());
+            if i <= 2 {
+                expected.push(i);
+                expected.push(0b1000_0000 | i);
+                expected.push(0b0100_0000 | i);
+                expected.push(0b1100_0000 | i);
+            }
+        }
+        let iter = tree.query(Boundary::new((-2, -2), 4, 4));
+        for i in iter {
+            expected.retain(|e| e != i)
+        }
+        assert!(expected.is_empty(), "items not found: {expected:?}")
+    }
+
+    #[test]
+    fn query_borrowed_boundary() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        assert!(tree.insert_at((1, 1), 1).is_ok());
+        assert!(tree.insert_at((5, 5), 2).is_ok());
+        assert!(tree.insert_at((9, 9), 3).is_ok());
+        let area = Boundary::new((0, 0), 5, 5);
+        let mut res = tree.query(&area).copied().collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(res, vec![1, 2]);
+        assert_eq!(tree.query_points(&area).count(), 2);
+    }
+
+    #[test]
+    fn query_blocks_mutation_through_ref_cell() {
+        let tree =
+            std::cell::RefCell::new(QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1));
+        assert!(tree.borrow_mut().insert_at((1, 1), 1).is_ok());
+        assert!(tree.borrow_mut().insert_at((8, 8), 2).is_ok());
+        let guard = tree.borrow();
+        let mut query = guard.query(Boundary::new((0, 0), 10, 10));
+        assert!(query.next().is_some());
+        assert!(tree.try_borrow_mut().is_err());
+        assert!(query.next().is_some());
+        drop(query);
+        drop(guard);
+        assert!(tree.borrow_mut().insert_at((5, 5), 3).is_ok());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "tree mutated during query")]
+    fn query_detects_mutation_in_debug_builds() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        assert!(tree.insert_at((1, 1), 1).is_ok());
+        assert!(tree.insert_at((8, 8), 2).is_ok());
+        let tree: *mut QuadTree<i32, i32> = &mut tree;
+        // Safety: not sound, this bypasses the borrow of the query on purpose to trigger the check
+        let mut query = unsafe { &*tree }.query(Boundary::new((0, 0), 10, 10));
+        assert!(query.next().is_some());
+        unsafe { &mut *tree }.clear();
+        query.next();
+    }
+
+    /// A segment between two points which counts its calls to `encloses`
+    #[derive(Clone)]
+    struct Segment {
+        from: Point<i32>,
+        to: Point<i32>,
+        encloses_calls: std::rc::Rc<std::cell::Cell<usize>>,
+    }
+
+    impl Area<i32> for Segment {
+        fn contains(&self, point: &Point<i32>) -> bool {
+            let (dx, dy) = (self.to.x - self.from.x, self.to.y - self.from.y);
+            let cross = dx * (point.y - self.from.y) - dy * (point.x - self.from.x);
+            cross == 0 && Boundary::between_points(self.from, self.to).contains(point)
+        }
+
+        fn intersects(&self, boundary: &Boundary<i32>) -> bool {
+            Boundary::between_points(self.from, self.to).intersects(boundary)
+        }
+
+        fn encloses(&self, boundary: &Boundary<i32>) -> bool {
+            self.encloses_calls.set(self.encloses_calls.get() + 1);
+            let (p1, p2) = (boundary.top_left(), boundary.bottom_right());
+            self.contains(p1) && self.contains(p2) && (p1.x == p2.x || p1.y == p2.y)
+        }
+
+        fn can_enclose(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn query_segment_skips_encloses() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 64, 64), 2);
+        let mut items = Vec::new();
+        for x in 0..=64 {
+            for y in (0..=64).step_by(3) {
+                assert!(tree.insert_at((x, y), (x, y)).is_ok());
+                items.push((x, y));
+            }
+        }
+        let segment = Segment {
+            from: Point::new(3, 60),
+            to: Point::new(60, 3),
+            encloses_calls: Default::default(),
+        };
+        let mut res = tree.query(segment.clone()).copied().collect::<Vec<_>>();
+        res.sort();
+        let expected = items
+            .iter()
+            .copied()
+            .filter(|p| segment.contains(&(*p).into()))
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(res, expected);
+
+        let mut visited = Vec::new();
+        tree.for_each_in_area(segment.clone(), |_, item| visited.push(*item));
+        let mut into_vec = Vec::new();
+        tree.query_into_vec(segment.clone(), &mut into_vec);
+        let folded = tree.try_fold_in_area(segment.clone(), 0, |count, _| Ok::<_, ()>(count + 1));
+        let coarse = tree.query_approx_coarse(segment.clone(), 0).count();
+        #[cfg(feature = "profiling")]
+        assert_eq!(tree.query_profiled(segment.clone()).0.len(), expected.len());
+        assert_eq!(visited.len(), expected.len());
+        assert_eq!(into_vec.len(), expected.len());
+        assert_eq!(folded, Ok(expected.len()));
+        assert_eq!(coarse, expected.len());
+        tree.clear_area(segment.clone());
+        assert_eq!(tree.len(), items.len() - expected.len());
+        assert_eq!(segment.encloses_calls.get(), 0);
+    }
+
+    #[test]
+    fn query_matches_naive_filter() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(709);
+        for _ in 0..50 {
+            let cap = rng.gen_range(1..=4);
+            let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 32, 32), cap);
+            let mut items = Vec::new();
+            for id in 0..rng.gen_range(0..300) {
+                // Small coordinates put many points on node borders
+                let p = Point::new(rng.gen_range(0..=32), rng.gen_range(0..=32));
+                assert!(tree.insert_at(p, id).is_ok());
+                items.push((p, id));
+            }
+            for _ in 0..50 {
+                let area = Boundary::between_points(
+                    (rng.gen_range(-2..=34), rng.gen_range(-2..=34)),
+                    (rng.gen_range(-2..=34), rng.gen_range(-2..=34)),
+                );
+                let mut expected = items
+                    .iter()
+                    .filter(|(p, _)| area.contains(p))
+                    .map(|(_, id)| *id)
+                    .collect::<Vec<_>>();
+                let mut res = tree.query(area).copied().collect::<Vec<_>>();
+                res.sort();
+                expected.sort();
+                assert_eq!(res, expected, "area {area}");
+            }
+        }
+    }
+
+    #[test]
+    fn query_indices() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(711);
+        let positions = (0..200)
+            .map(|_| Point::new(rng.gen_range(0..=50), rng.gen_range(0..=50)))
+            .collect::<Vec<_>>();
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 50, 50), 4);
+        for (entity, position) in positions.iter().enumerate() {
+            assert!(tree.insert_at(*position, entity).is_ok());
+        }
+        let area = Boundary::new((10, 20), 15, 10);
+        let mut res = tree.query_indices(area).collect::<Vec<_>>();
+        res.sort();
+        let expected = (0..positions.len())
+            .filter(|entity| area.contains(&positions[*entity]))
+            .collect::<Vec<_>>();
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn query_budgeted() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 2);
+        for i in 0..=20 {
+            for j in 0..=20 {
+                assert!(tree.insert_at((i, j), (i, j)).is_ok());
+            }
+        }
+        let area = Boundary::new((3, 3), 12, 12);
+        let full = tree.query(area).collect::<Vec<_>>();
+        let (partial, truncated) = tree.query_budgeted(area, 5);
+        assert!(truncated);
+        assert!(partial.len() < full.len());
+        assert!(partial.iter().all(|item| full.contains(item)));
+        let (res, truncated) = tree.query_budgeted(area, usize::MAX);
+        assert!(!truncated);
+        let mut res = res.into_iter().copied().collect::<Vec<_>>();
+        let mut full = full.into_iter().copied().collect::<Vec<_>>();
+        res.sort();
+        full.sort();
+        assert_eq!(res, full);
+        assert_eq!(tree.query_budgeted(area, 0), (vec![], true));
+    }
+
+    #[test]
+    fn query_copied_points() {
+        fn in_area(tree: &QuadTree<i32, u32>, area: Boundary<i32>) -> Vec<(Point<i32>, u32)> {
+            tree.query_copied_points(area).collect()
+        }
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        for i in 0..=10 {
+            assert!(tree.insert_at((i, 10 - i), i as u32).is_ok());
+        }
+        let mut res = in_area(&tree, Boundary::new((0, 0), 4, 10));
+        tree.clear_area(Boundary::new((0, 0), 10, 10));
+        assert!(tree.is_empty());
+        res.sort();
+        assert_eq!(
+            res,
+            (0..=4)
+                .map(|i| (Point::new(i, 10 - i), i as u32))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn iter_points_where() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 3);
+        for x in 0..=20 {
+            for y in 0..=20 {
+                assert!(tree.insert_at((x, y), (x, y)).is_ok());
+            }
+        }
+        let res = tree
+            .iter_points_where(|p| p.x % 2 == 0 && p.y % 3 == 0)
+            .collect::<Vec<_>>();
+        assert_eq!(res.len(), 11 * 7);
+        assert!(res.iter().all(|(x, y)| x % 2 == 0 && y % 3 == 0));
+    }
+
+    #[test]
+    fn query_chunks() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 3);
+        for i in 0..=20 {
+            for j in 0..=20 {
+                assert!(tree.insert_at((i, j), (i, j)).is_ok());
+            }
+        }
+        let area = Boundary::new((2, 3), 10, 4);
+        let chunks = tree.query_chunks(area, 8).collect::<Vec<_>>();
+        // 11 * 5 items
+        assert_eq!(chunks.len(), 7);
+        assert!(chunks[..6].iter().all(|c| c.len() == 8));
+        assert_eq!(chunks[6].len(), 7);
+        let flat = chunks.into_iter().flatten().collect::<Vec<_>>();
+        assert_eq!(flat, tree.query(area).collect::<Vec<_>>());
+        assert_eq!(
+            tree.query_chunks(Boundary::new((30, 30), 1, 1), 8).count(),
+            0
+        );
+        let all = tree.query_chunks(area, usize::MAX).collect::<Vec<_>>();
+        assert_eq!(all, vec![flat]);
+    }
+
+    #[test]
+    fn weighted_count() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 100., 100.), 4);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(737);
+        let mut points = Vec::new();
+        for i in 0..500 {
+            let p = Point::new(rng.gen_range(0. ..=100.), rng.gen_range(0. ..=100.));
+            assert!(tree.insert_at(p, i).is_ok());
+            points.push(p);
+        }
+        let area = Boundary::new((20., 30.), 40., 30.);
+        assert_eq!(
+            tree.weighted_count(area, |_| 1.),
+            tree.query(area).count() as f64
+        );
+        // Gaussian falloff around the center of the area
+        let center = Point::new(40., 45.);
+        let gaussian = |p: &Point<f64>| {
+            let d = Euclidean::distance(&center, p);
+            (-d * d / 200.).exp()
+        };
+        let expected = points
+            .iter()
+            .filter(|p| area.contains(p))
+            .map(gaussian)
+            .sum::<f64>();
+        assert!((tree.weighted_count(area, gaussian) - expected).abs() < 1e-9);
+        assert_eq!(
+            tree.weighted_count(Boundary::new((200., 200.), 1., 1.), gaussian),
+            0.
+        );
+    }
+
+    #[test]
+    fn for_each_in_area() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((-10, -10), 20, 20), 2);
+        for i in -10..=10 {
+            for j in -10..=10 {
+                assert!(tree.insert_at((i, j), (i, j)).is_ok());
+            }
+        }
+        for area in [
+            Boundary::new((-2, -2), 4, 4),
+            Boundary::new((-10, -10), 20, 20),
+            Boundary::new((3, -8), 1, 15),
+            Boundary::new((30, 30), 1, 1),
+        ] {
+            let mut res = Vec::new();
+            tree.for_each_in_area(area, |point, item| {
+                assert_eq!(*point, Point::from(*item));
+                res.push(*item)
+            });
+            let mut expected = tree.query(area).copied().collect::<Vec<_>>();
+            res.sort();
+            expected.sort();
+            assert_eq!(res, expected);
+        }
+    }
+
+    #[test]
+    fn try_fold_in_area_sum() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 3);
+        for i in 0..=20 {
+            for j in 0..=20 {
+                assert!(tree.insert_at((i, j), i * j).is_ok());
+            }
+        }
+        let area = Boundary::new((2, 5), 7, 3);
+        let expected: i32 = tree.query(area).sum();
+        let sum = tree.try_fold_in_area(area, 0, |sum, item| Ok::<_, ()>(sum + item));
+        assert_eq!(sum, Ok(expected));
+    }
+
+    #[test]
+    fn try_fold_in_area_abort() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 3);
+        for i in 0..=20 {
+            for j in 0..=20 {
+                assert!(tree.insert_at((i, j), i * j).is_ok());
+            }
+        }
+        let mut visited = 0;
+        let res = tree.try_fold_in_area(Boundary::new((0, 0), 20, 20), 0, |sum, item| {
+            visited += 1;
+            if visited == 10 {
+                Err(*item)
+            } else {
+                Ok(sum + item)
+            }
+        });
+        assert!(res.is_err());
+        assert_eq!(visited, 10);
+    }
+
+    #[test]
+    fn query_f64() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 2);
+        for i in 0..=20 {
+            for j in 0..=20 {
+                assert!(tree.insert_at((i, j), (i, j)).is_ok());
+            }
+        }
+        let mut res = tree
+            .query_f64(Boundary::between_points((2.5, 3.9), (4.5, 5.1)))
+            .into_iter()
+            .copied()
+            .collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(res, vec![(3, 4), (3, 5), (4, 4), (4, 5)]);
+        assert!(tree
+            .query_f64(Boundary::between_points((2.1, 2.1), (2.9, 2.9)))
+            .is_empty());
+        assert_eq!(
+            tree.query_f64(Boundary::between_points((-0.5, -0.5), (20.5, 20.5)))
+                .len(),
+            21 * 21
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((-50, -50), 100, 100), 3);
+        let mut const_tree =
+            QuadTree::<_, _, ConstCap<3>>::new_with_const_cap(Boundary::new((0., 0.), 1., 1.));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(764);
+        for i in 0..200 {
+            let p = Point::new(rng.gen_range(-50..=50), rng.gen_range(-50..=50));
+            assert!(tree.insert_at(p, format!("item {i}")).is_ok());
+            let p = Point::new(rng.gen_range(0. ..=1.), rng.gen_range(0. ..=1.));
+            assert!(const_tree.insert_at(p, i).is_ok());
+        }
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(
+            serde_json::from_str::<QuadTree<i32, String>>(&json).unwrap(),
+            tree
+        );
+        let json = serde_json::to_string(&const_tree).unwrap();
+        assert_eq!(
+            serde_json::from_str::<QuadTree<f64, i32, ConstCap<3>>>(&json).unwrap(),
+            const_tree
+        );
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn query_smallvec() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 2);
+        for i in 0..=20 {
+            for j in 0..=20 {
+                assert!(tree.insert_at((i, j), (i, j)).is_ok());
+            }
+        }
+        let small = Boundary::new((3, 4), 1, 1);
+        let res = tree.query_smallvec::<_, 4>(small);
+        assert!(!res.spilled());
+        let mut res = res.into_iter().copied().collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(res, vec![(3, 4), (3, 5), (4, 4), (4, 5)]);
+
+        let large = Boundary::new((2, 2), 10, 5);
+        let res = tree.query_smallvec::<_, 4>(large);
+        assert!(res.spilled());
+        let mut res = res.into_iter().copied().collect::<Vec<_>>();
+        let mut expected = tree.query(large).copied().collect::<Vec<_>>();
+        res.sort();
+        expected.sort();
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn query_bounds() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        for _ in 0..300 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, p).is_ok());
+        }
+        let area = Boundary::new((20, 30), 40, 25);
+        let bounds = tree.query_bounds(area).unwrap();
+        let points = tree.query(area).collect::<Vec<_>>();
+        assert!(points.iter().all(|p| bounds.contains(p)));
+        assert!(points.iter().any(|p| p.x == bounds.left_border()));
+        assert!(points.iter().any(|p| p.x == bounds.right_border()));
+        assert!(points.iter().any(|p| p.y == bounds.top_border()));
+        assert!(points.iter().any(|p| p.y == bounds.bottom_border()));
+    }
+
+    #[test]
+    fn query_soa() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        for i in 0..10 {
+            assert!(tree.insert_at((i, 9 - i), i).is_ok());
+        }
+        let area = Boundary::new((2, 2), 5, 5);
+        let (xs, ys, items) = tree.query_soa(area);
+        assert_eq!(xs.len(), items.len());
+        assert_eq!(ys.len(), items.len());
+        let expected = tree.query_points(area).collect::<Vec<_>>();
+        assert_eq!(expected.len(), items.len());
+        for (i, (point, item)) in expected.into_iter().enumerate() {
+            assert_eq!((xs[i], ys[i], items[i]), (point.x, point.y, item));
+        }
+    }
+
+    #[test]
+    fn query_limited() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        for i in 0..10 {
+            assert!(tree.insert_at((i, i), i).is_ok());
+        }
+        assert_eq!(
+            tree.query_limited(Boundary::new((0, 0), 10, 10), 3).len(),
+            3
+        );
+        let sparse = tree.query_limited(Boundary::new((0, 0), 1, 1), 5);
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(
+            tree.query_limited(Boundary::new((0, 0), 10, 10), 0).len(),
+            0
+        );
+        assert_eq!(
+            tree.query_limited(Boundary::new((0, 0), 10, 10), usize::MAX)
+                .len(),
+            10
+        );
     }
 
-    /// Returns the boundary of this QuadTree
-    pub fn boundary(&self) -> &Boundary<C> {
-        &self.boundary
+    #[test]
+    fn query_with_distance() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 10., 10.), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(13);
+        for _ in 0..100 {
+            let p = Point::new(rng.gen_range(0.0..=10.), rng.gen_range(0.0..=10.));
+            assert!(tree.insert_at(p, p).is_ok());
+        }
+        let focus = Point::new(7.5f64, 2.5);
+        let area = Boundary::new((2., 2.), 6., 6.);
+        let res = tree.query_with_distance(area, focus).collect::<Vec<_>>();
+        assert_eq!(res.len(), tree.query(area).count());
+        for (p, distance) in res {
+            assert!(distance >= 0.);
+            let expected = ((p.x - focus.x).powi(2) + (p.y - focus.y).powi(2)).sqrt();
+            assert!((distance - expected).abs() < 1e-12);
+        }
     }
 
-    /// Returns the capacity
-    pub fn capacity(&self) -> usize {
-        self.capacity.capacity()
+    #[test]
+    fn query_except() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 3);
+        for x in 0..=20 {
+            for y in 0..=20 {
+                assert!(tree.insert_at((x, y), (x, y)).is_ok());
+            }
+        }
+        let include = Boundary::new((2, 2), 16, 16);
+        let exclude = Boundary::new((8, 8), 4, 4);
+        let res = tree.query_except(include, exclude).collect::<Vec<_>>();
+        assert_eq!(res.len(), 17 * 17 - 5 * 5);
+        for (x, y) in res {
+            assert!(include.contains(&(*x, *y).into()));
+            assert!(!exclude.contains(&(*x, *y).into()));
+        }
     }
-}
 
-impl<C, Item, Cap> QuadTree<C, Item, Cap>
-where
-    Cap: Capacity,
-    C: Coordinate,
-    Item: AsPoint<C>,
-{
-    /// Insert a new item
-    /// # Errors
-    /// Returns an error if the item is out of bounds.
-    /// # Example
-    /// ```
-    /// use qutee::*;
-    /// struct Item {
-    ///     x: usize,
-    ///     y: usize,
-    /// }
-    /// impl AsPoint<usize> for Item {
-    ///     fn as_point(&self) -> Point<usize> {
-    ///         (self.x, self.y).into()
-    ///     }
-    /// }
-    /// let mut quad_tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0,0),(10,10)), 5);
-    /// assert!(quad_tree.insert(Item {
-    ///     x: 5,
-    ///     y: 5,
-    /// }).is_ok());
-    /// ```
-    pub fn insert(&mut self, item: Item) -> Result<(), QuadTreeError<C>> {
-        self.insert_at(item.as_point(), item)
+    #[test]
+    fn query_hilbert() {
+        let curve = super::hilbert::ORDER_2_CURVE;
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 40, 40), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(728);
+        let mut shuffled = curve.iter().enumerate().collect::<Vec<_>>();
+        for i in (1..shuffled.len()).rev() {
+            shuffled.swap(i, rng.gen_range(0..=i));
+        }
+        for (i, (x, y)) in shuffled {
+            // Cell centers of the grid
+            assert!(tree.insert_at((x * 10 + 5, y * 10 + 5), i).is_ok());
+        }
+        let all = Boundary::new((0, 0), 40, 40);
+        let expected = (0..curve.len()).collect::<Vec<_>>();
+        assert_eq!(
+            tree.query_hilbert(all)
+                .into_iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(tree.query_hilbert(all), tree.query_hilbert(all));
+        // Filtering keeps the order
+        let top = Boundary::new((0, 0), 40, 19);
+        assert_eq!(
+            tree.query_hilbert(top),
+            vec![&0, &1, &2, &3, &12, &13, &14, &15]
+        );
     }
 
-    /// Same as `insert` except that no bounds check is performed.
-    pub fn insert_unchecked(&mut self, item: Item) {
-        self.insert_at_unchecked(item.as_point(), item)
+    #[test]
+    fn query_approx_coarse() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 256, 256), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(731);
+        for i in 0..1_000 {
+            let p = Point::new(rng.gen_range(0..=256), rng.gen_range(0..=256));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        let mut had_false_positives = false;
+        for _ in 0..50 {
+            let area = Boundary::between_points(
+                (rng.gen_range(-2..=258), rng.gen_range(-2..=258)),
+                (rng.gen_range(-2..=258), rng.gen_range(-2..=258)),
+            );
+            let exact = tree.query(area).copied().collect::<HashSet<_>>();
+            assert_eq!(
+                tree.query_approx_coarse(area, 0)
+                    .copied()
+                    .collect::<HashSet<_>>(),
+                exact
+            );
+            let approx = tree
+                .query_approx_coarse(area, 1_000)
+                .copied()
+                .collect::<Vec<_>>();
+            let approx_set = approx.iter().copied().collect::<HashSet<_>>();
+            assert_eq!(approx.len(), approx_set.len(), "items are returned once");
+            assert!(approx_set.is_superset(&exact), "area {area}");
+            had_false_positives |= approx_set.len() > exact.len();
+        }
+        assert!(had_false_positives);
     }
-}
 
-impl<C, Item> QuadTree<C, Item, DynCap>
-where
-    C: Coordinate,
-{
-    /// Create a new QuadTree
-    pub fn new_with_dyn_cap(boundary: Boundary<C>, cap: usize) -> Self {
-        Self::new_with_capacity(boundary, DynCap(cap))
+    #[test]
+    fn query_approx_coarse_large_boundary() {
+        // The area of the root does not fit into an i32
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100_000, 100_000), 1);
+        assert!(tree.insert_at((10, 10), 1).is_ok());
+        assert!(tree.insert_at((90_000, 90_000), 2).is_ok());
+        let area = Boundary::new((0, 0), 20, 20);
+        assert_eq!(
+            tree.query_approx_coarse(area, 0).collect::<Vec<_>>(),
+            vec![&1]
+        );
+        assert_eq!(
+            tree.query_approx_coarse(area, i32::MAX).collect::<Vec<_>>(),
+            vec![&1]
+        );
     }
-}
 
-impl<C, Item, const CAP: usize> QuadTree<C, Item, ConstCap<CAP>>
-where
-    C: Coordinate,
-{
-    /// Create a new QuadTree with a constant capacity
-    pub fn new_with_const_cap(boundary: Boundary<C>) -> Self {
-        let capacity = ConstCap;
-        Self::new_with_capacity(boundary, capacity)
+    #[test]
+    fn query_dedup() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 1);
+        // One item covering four cells
+        for p in [(20, 20), (70, 20), (20, 70), (70, 70)] {
+            assert!(tree.insert_at(p, "building").is_ok());
+        }
+        assert!(tree.insert_at((50, 50), "tree").is_ok());
+        assert!(tree.insert_at((95, 95), "lake").is_ok());
+        let area = Boundary::new((10, 10), 70, 70);
+        assert_eq!(tree.query(area).count(), 5);
+        let mut res = tree.query_dedup(area).collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(res, vec![&"building", &"tree"]);
     }
-}
 
-impl<C> From<(C, C)> for Point<C>
-where
-    C: Coordinate,
-{
-    fn from((x, y): (C, C)) -> Self {
-        Point { x, y }
+    #[test]
+    fn query_into_vec_reuses_buffer() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 4);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(745);
+        for i in 0..500 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        let mut out = Vec::new();
+        for area in [
+            Boundary::between_points((10, 10), (60, 40)),
+            Boundary::between_points((50, 0), (100, 100)),
+        ] {
+            tree.query_into_vec(area, &mut out);
+            let mut expected = tree.query(area).collect::<Vec<_>>();
+            expected.sort();
+            out.sort();
+            assert_eq!(out, expected);
+        }
+        let capacity = out.capacity();
+        tree.query_into_vec(Boundary::between_points((10, 10), (20, 20)), &mut out);
+        assert_eq!(out.capacity(), capacity);
     }
-}
 
-impl<C> Display for Point<C>
-where
-    C: Coordinate,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({:?},{:?})", self.x, self.y)
+    #[test]
+    fn remove_at() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(751);
+        let mut points = Vec::new();
+        for i in 0..300 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
+            points.push((p, i));
+        }
+        assert!(tree.depth() > 0);
+        assert_eq!(tree.remove_at((-1, 50)), None);
+        assert_eq!(tree.remove_at((50, 101)), None);
+        for (i, (point, _)) in points.iter().enumerate() {
+            let removed = tree.remove_at(*point).expect("item at point");
+            assert!(points[i..].contains(&(*point, removed)));
+            assert_eq!(tree.len(), points.len() - i - 1);
+            assert_consistent(&tree);
+        }
+        assert_eq!(tree.depth(), 0);
+        assert_eq!(tree.remove_at((50, 50)), None);
     }
-}
 
-impl<C> Error for QuadTreeError<C> where C: Coordinate {}
+    #[test]
+    fn remove_item() {
+        #[derive(Debug, PartialEq)]
+        struct Entity {
+            id: usize,
+            pos: Point<i32>,
+        }
+        impl AsPoint<i32> for Entity {
+            fn as_point(&self) -> Point<i32> {
+                self.pos
+            }
+        }
 
-impl<C> Display for QuadTreeError<C>
-where
-    C: Coordinate,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        <Self as Debug>::fmt(self, f)
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(752);
+        for id in 0..300 {
+            let pos = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert(Entity { id, pos }).is_ok());
+        }
+        let mut entities = tree
+            .iter_points()
+            .map(|(pos, e)| (e.id, *pos))
+            .collect::<Vec<_>>();
+        entities.sort();
+        assert!(!tree.remove_item(&Entity {
+            id: 0,
+            pos: Point::new(-1, 0)
+        }));
+        assert!(!tree.remove_item(&Entity {
+            id: 300,
+            pos: entities[0].1
+        }));
+        for (i, (id, pos)) in entities.iter().enumerate() {
+            assert!(tree.remove_item(&Entity { id: *id, pos: *pos }));
+            assert!(!tree.remove_item(&Entity { id: *id, pos: *pos }));
+            assert_eq!(tree.len(), entities.len() - i - 1);
+            assert_consistent(&tree);
+        }
+        assert_eq!(tree.depth(), 0);
     }
-}
 
-impl<C> Debug for QuadTreeError<C>
-where
-    C: Coordinate,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::OutOfBounds(boundary, point) => {
-                write!(f, "point {point} is outside of area {boundary}")
-            }
+    #[test]
+    fn clear() {
+        let boundary = Boundary::new((0, 0), 100, 100);
+        let mut tree = QuadTree::new_with_dyn_cap(boundary, 2).with_preallocated_items(true);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(754);
+        for i in 0..100 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
         }
+        tree.clear();
+        assert_eq!(tree.iter().count(), 0);
+        assert_eq!(tree.boundary(), &boundary);
+        assert_eq!(tree.capacity(), 2);
+        assert_eq!(
+            tree,
+            QuadTree::new_with_dyn_cap(boundary, 2).with_preallocated_items(true)
+        );
+        assert!(tree.insert_at((50, 50), 100).is_ok());
+        assert_eq!(tree.len(), 1);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::{bounds::ConstCap, Boundary, Coordinate, Point, QuadTree, QuadTreeError};
 
     #[test]
-    fn create_quad_tree() {
-        let boundary = Boundary::new((0, 0), 10, 10);
-        let tree = QuadTree::<usize, u8, ConstCap<20>>::new_with_const_cap(boundary.clone());
+    fn identical_points_beyond_capacity() {
+        let mut tree =
+            QuadTree::<_, _, ConstCap<4>>::new_with_const_cap(Boundary::new((0, 0), 10, 10));
+        for i in 0..100 {
+            assert!(tree.insert_at((5, 5), i).is_ok());
+        }
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.validate(), Ok(()));
+
+        let boundary = Boundary::new((0., 0.), 10., 10.);
+        let mut unlimited = QuadTree::<_, _, ConstCap<4>>::new_with_const_cap(boundary);
+        let mut limited =
+            QuadTree::<_, _, ConstCap<4>>::new_with_const_cap(boundary).with_max_depth(8);
+        for i in 0..100 {
+            assert!(unlimited.insert_at((5., 5.), i).is_ok());
+            assert!(limited.insert_at((5., 5.), i).is_ok());
+        }
+        assert_eq!(unlimited.depth(), 24);
+        assert_eq!(limited.depth(), 8);
+        assert_eq!(limited.len(), 100);
+        assert_eq!(limited.validate(), Ok(()));
         assert_eq!(
-            QuadTree {
-                boundary,
-                quadrants: None,
-                items: None,
-                capacity: ConstCap,
-            },
-            tree
+            limited
+                .query(Boundary::new((4., 4.), 2., 2.))
+                .copied()
+                .collect::<HashSet<_>>(),
+            (0..100).collect()
         );
-        assert_eq!(None, tree.items)
     }
 
     #[test]
-    fn insert_single() {
-        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 10);
-        assert!(tree.insert_at((10, 10), 1u8).is_ok());
-        assert_eq!(tree.items.unwrap()[0], ((10, 10).into(), 1));
+    fn nearest_to() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 1);
+        for (i, point) in [(20, 20), (80, 20), (20, 80), (80, 80)]
+            .into_iter()
+            .enumerate()
+        {
+            assert!(tree.insert_at(point, i).is_ok());
+        }
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.nearest_to((0, 0)), Some(&0));
+        assert_eq!(tree.nearest_to((100, 0)), Some(&1));
+        assert_eq!(tree.nearest_to((45, 100)), Some(&2));
+        assert_eq!(tree.nearest_to((60, 55)), Some(&3));
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(760);
+        for i in 4..500 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        for _ in 0..50 {
+            let target = Point::new(rng.gen_range(-20..=120), rng.gen_range(-20..=120));
+            let (nearest, _) = tree
+                .iter_points()
+                .find(|(_, i)| Some(i) == tree.nearest_to(target))
+                .unwrap();
+            let best = tree
+                .iter_points()
+                .map(|(p, _)| Euclidean::distance(&target, p))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(Euclidean::distance(&target, nearest), best);
+        }
     }
 
     #[test]
-    fn insert_out_of_bounds() {
-        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 10);
+    fn k_nearest() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let points = [
+            (50, 50),
+            (52, 50),
+            (50, 55),
+            (40, 40),
+            (90, 10),
+            (10, 90),
+            (60, 70),
+        ];
+        for (i, point) in points.into_iter().enumerate() {
+            assert!(tree.insert_at(point, i).is_ok());
+        }
+        assert_eq!(tree.k_nearest((51, 51), 3), vec![&0, &1, &2]);
+        assert_eq!(tree.k_nearest((100, 0), 1), vec![&4]);
+        assert_eq!(tree.k_nearest((51, 51), 0), Vec::<&usize>::new());
         assert_eq!(
-            tree.insert_at((20, 20), 1u8),
-            Err(QuadTreeError::OutOfBounds(
-                Boundary::new((0, 0), 10, 10),
-                (20, 20).into()
-            ))
+            tree.k_nearest((0, 100), 10),
+            vec![&5, &6, &2, &0, &3, &1, &4]
+        );
+        assert_eq!(
+            tree.k_nearest((0, 100), usize::MAX),
+            vec![&5, &6, &2, &0, &3, &1, &4]
         );
     }
 
     #[test]
-    fn insert_more_than_capacity() {
-        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
-        assert!(tree.quadrants.is_none());
-
-        assert!(tree.insert_at((1, 1), 1).is_ok());
-        assert!(tree.quadrants.is_none());
-        assert_eq!(tree.items.as_ref().unwrap().len(), 1);
-
-        assert!(tree.insert_at((2, 2), 1).is_ok());
-        assert_eq!(tree.items.as_ref().unwrap().len(), 1);
-        assert!(tree.quadrants.is_some());
-        let quads = tree.quadrants.as_ref().unwrap();
-        assert_eq!(quads[0].items.as_ref().unwrap().len(), 1);
-        assert_eq!(quads[1].items, None);
-        assert_eq!(quads[2].items, None);
-        assert_eq!(quads[3].items, None);
+    fn iter_mut() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(763);
+        for i in 0..300 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        let before = tree.iter_points().cloned().collect::<Vec<_>>();
+        assert_eq!(tree.iter_mut().count(), 300);
+        tree.iter_mut().for_each(|item| *item *= 2);
+        let after = tree.iter_points().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            after,
+            before
+                .into_iter()
+                .map(|(p, i)| (p, i * 2))
+                .collect::<Vec<_>>()
+        );
+        assert_consistent(&tree);
+    }
 
-        assert!(tree.insert_at((7, 7), 1).is_ok());
-        assert!(tree.quadrants.is_some());
-        let quads = tree.quadrants.as_ref().unwrap();
-        assert_eq!(quads[0].items.as_ref().unwrap().len(), 1);
-        assert_eq!(quads[1].items, None);
-        assert_eq!(quads[2].items, None);
-        assert_eq!(quads[3].items.as_ref().unwrap().len(), 1);
+    #[test]
+    fn query_leaves() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 128, 128), 1);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(742);
+        for i in 0..200 {
+            let p = Point::new(rng.gen_range(0..=128), rng.gen_range(0..=128));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        let area = Boundary::new((30, 40), 20, 50);
+        let leaves = tree.query_leaves(area).collect::<Vec<_>>();
+        assert!(leaves.len() > 1);
+        assert!(leaves.iter().all(|leaf| area.intersects(leaf)));
+        // Every item in the area lies in one of the leaves
+        for (point, _) in tree.query_points(area) {
+            assert!(leaves.iter().any(|leaf| leaf.contains(point)), "{point}");
+        }
+        assert_eq!(tree.query_leaves(tree.boundary).count(), {
+            let mut leaves = 0;
+            let mut stack = vec![&tree];
+            while let Some(node) = stack.pop() {
+                match &node.quadrants {
+                    Some(quads) => stack.extend(quads.iter()),
+                    None => leaves += 1,
+                }
+            }
+            leaves
+        });
+        assert_eq!(
+            tree.query_leaves(Boundary::new((200, 200), 1, 1)).count(),
+            0
+        );
     }
 
     #[test]
-    fn query() {
-        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((-10, -10), 20, 20), 2);
-        let mut expected = Vec::new();
-        for i in 1..10 {
+    fn query_values() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        for i in 0..10 {
             assert!(tree.insert_at((i, i), i).is_ok());
-            assert!(tree.insert_at((-i, i), 0b1000_0000 | i).is_ok());
-            assert!(tree.insert_at((i, -i), 0b0100_0000 | i).is_ok());
-            assert!(tree.insert_at((-i, -i), 0b1100_0000 | i).is_ok());
-            if i <= 2 {
-                expected.push(i);
-                expected.push(0b1000_0000 | i);
-                expected.push(0b0100_0000 | i);
-                expected.push(0b1100_0000 | i);
-            }
         }
-        let iter = tree.query(Boundary::new((-2, -2), 4, 4));
-        for i in iter {
-            expected.retain(|e| e != i)
+        let wanted = HashSet::from([1, 3, 8]);
+        let mut res = tree
+            .query_values(Boundary::new((0, 0), 5, 5), &wanted)
+            .copied()
+            .collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(res, vec![1, 3]);
+    }
+
+    #[test]
+    fn query_is_spatially_coherent() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 16, 16), 1);
+        // Stays in the root node
+        assert!(tree.insert_at((8, 8), None).is_ok());
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        for _ in 0..200 {
+            let p: Point<i32> = (rng.gen_range(0..=16), rng.gen_range(0..=16)).into();
+            let quadrant = (p.y > 8) as usize * 2 + (p.x > 8) as usize;
+            assert!(tree.insert_at(p, Some(quadrant)).is_ok());
         }
-        assert!(expected.is_empty(), "items not found: {expected:?}")
+        let res = tree
+            .query(Boundary::new((0, 0), 16, 16))
+            .collect::<Vec<_>>();
+        assert_eq!(res.last(), Some(&&None));
+        let quadrants = res.iter().flat_map(|q| **q).collect::<Vec<_>>();
+        assert!(quadrants.windows(2).all(|w| w[0] <= w[1]), "{quadrants:?}");
+        assert_eq!(quadrants.first(), Some(&0));
+        assert_eq!(quadrants.last(), Some(&3));
     }
 
     #[test]
@@ -462,6 +4643,78 @@ mod tests {
         assert!(expected.is_empty(), "items not found: {expected:?}")
     }
 
+    #[test]
+    fn iterators_are_fused() {
+        fn assert_fused<T>(mut iter: impl std::iter::FusedIterator<Item = T>, len: usize) {
+            assert_eq!(iter.by_ref().take(len).count(), len);
+            for _ in 0..5 {
+                assert!(iter.next().is_none());
+            }
+        }
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        for i in 0..10 {
+            assert!(tree.insert_at((i, i), i).is_ok());
+        }
+        let area = Boundary::new((0, 0), 4, 4);
+        assert_fused(tree.iter(), 10);
+        assert_fused(tree.iter_points(), 10);
+        assert_fused(tree.iter_with_path(), 10);
+        assert_fused(tree.query(area), 5);
+        assert_fused(tree.query_points(area), 5);
+    }
+
+    #[test]
+    fn iter_with_path() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 16, 16), 1);
+        for x in 0..16 {
+            for y in 0..16 {
+                assert!(tree.insert_at((x, y), (x, y)).is_ok());
+            }
+        }
+        let res = tree.iter_with_path().collect::<Vec<_>>();
+        assert_eq!(res.len(), 256);
+        for (path, (x, y)) in res {
+            match path.first() {
+                None => assert_eq!((*x, *y), (0, 0)),
+                Some(0) => assert!(*x <= 8 && *y <= 8, "{x},{y}"),
+                Some(1) => assert!(*x >= 8 && *y <= 8, "{x},{y}"),
+                Some(2) => assert!(*x <= 8 && *y >= 8, "{x},{y}"),
+                Some(3) => assert!(*x >= 8 && *y >= 8, "{x},{y}"),
+                Some(i) => panic!("invalid quadrant {i}"),
+            }
+            assert!(path.iter().all(|i| *i < 4));
+        }
+    }
+
+    #[test]
+    fn histogram() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 12, 12), 3);
+        for x in 0..=12 {
+            for y in 0..=12 {
+                assert!(tree.insert_at((x, y), ()).is_ok());
+            }
+        }
+        let histogram = tree.histogram(3, 2);
+        assert_eq!(histogram.len(), 2);
+        assert!(histogram.iter().all(|row| row.len() == 3));
+        assert_eq!(histogram.iter().flatten().sum::<usize>(), tree.len());
+        // Columns start at 0, 4 and 8 with the border at 12 counted in the last column.
+        // Rows start at 0 and 6 with the border at 12 counted in the last row.
+        assert_eq!(histogram, vec![vec![24, 24, 30], vec![28, 28, 35]]);
+        assert!(tree.histogram(0, 2).iter().all(|row| row.is_empty()));
+    }
+
+    #[test]
+    fn len() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        assert!(tree.is_empty());
+        for i in 0..10 {
+            assert!(tree.insert_at((i, i), i).is_ok());
+            assert_eq!(tree.len(), i as usize + 1);
+        }
+        assert!(!tree.is_empty());
+    }
+
     #[test_case::test_case(10, 10 => Point {x: 10, y: 10}; "int")]
     #[test_case::test_case(10., 10. => Point {x: 10., y: 10.}; "float")]
     fn tuple_to_point<Pu>(x: Pu, y: Pu) -> Point<Pu>
@@ -471,6 +4724,33 @@ mod tests {
         (x, y).into()
     }
 
+    #[test]
+    fn sort_points() {
+        let mut points = [(2, 1), (1, 5), (2, -3), (-1, 0), (1, 4)].map(Point::from);
+        points.sort();
+        assert_eq!(
+            points,
+            [(-1, 0), (1, 4), (1, 5), (2, -3), (2, 1)].map(Point::from)
+        );
+        let map = std::collections::BTreeMap::from(points.map(|p| (p, p.x + p.y)));
+        assert_eq!(map.keys().next(), Some(&Point::new(-1, 0)));
+    }
+
+    #[test]
+    fn clamp_out_of_bounds_error() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 10., 10.), 2);
+        let err = tree.insert_at((-3., 14.), 1).unwrap_err();
+        let clamped = err.clamped_to(tree.boundary());
+        assert_eq!(clamped, Some(Point::new(0., 10.)));
+        assert!(tree.insert_at(clamped.unwrap(), 1).is_ok());
+        assert_eq!(tree.query(Boundary::new((0., 10.), 0., 0.)).count(), 1);
+
+        let err = QuadTreeError::OutOfBounds(*tree.boundary(), Point::new(f64::NAN, 5.));
+        assert_eq!(err.clamped_to(tree.boundary()), None);
+        let err = QuadTreeError::InvalidPoint(Point::new(5., f64::INFINITY));
+        assert_eq!(err.clamped_to(tree.boundary()), None);
+    }
+
     #[test]
     fn format_debug_error() {
         let e = super::QuadTreeError::OutOfBounds(
@@ -495,6 +4775,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn custom_point_type() {
+        #[derive(Debug, PartialEq)]
+        struct Vec2 {
+            x: f32,
+            y: f32,
+        }
+        impl super::IntoPoint<f32> for Vec2 {
+            fn into_point(self) -> Point<f32> {
+                Point::new(self.x, self.y)
+            }
+        }
+        impl super::FromPoint<f32> for Vec2 {
+            fn from_point(p: Point<f32>) -> Self {
+                Vec2 { x: p.x, y: p.y }
+            }
+        }
+        let mut tree = QuadTree::new_with_dyn_cap(
+            Boundary::between_points(Vec2 { x: 0., y: 0. }, Vec2 { x: 10., y: 10. }),
+            2,
+        );
+        assert!(tree.insert_at(Vec2 { x: 1., y: 2. }, "a").is_ok());
+        assert!(tree.insert_at(Vec2 { x: 8., y: 9. }, "b").is_ok());
+        let res = tree
+            .query_points_as::<Vec2, _>(Boundary::new(Vec2 { x: 0., y: 0. }, 5., 5.))
+            .collect::<Vec<_>>();
+        assert_eq!(res, vec![(Vec2 { x: 1., y: 2. }, &"a")]);
+    }
+
     #[test]
     fn insert_item() {
         struct TmpItem {
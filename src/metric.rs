@@ -0,0 +1,129 @@
+use crate::{boundary::to_f64, Boundary, Coordinate, Point};
+
+/// A distance metric used by the nearest neighbor queries.
+///
+/// `min_distance_to_boundary` must never be larger than the distance to any point inside the boundary.
+/// Otherwise nearest neighbor queries may skip the correct result.
+pub trait Metric<C>
+where
+    C: Coordinate,
+{
+    /// Distance between two points
+    fn distance(a: &Point<C>, b: &Point<C>) -> f64;
+    /// Smallest possible distance between a point and any point inside the boundary
+    fn min_distance_to_boundary(point: &Point<C>, boundary: &Boundary<C>) -> f64;
+}
+
+/// Straight line distance
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct Euclidean;
+
+/// Sum of the distances along both axes
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct Manhattan;
+
+/// Largest distance along either axis
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct Chebyshev;
+
+impl<C> Metric<C> for Euclidean
+where
+    C: Coordinate,
+{
+    fn distance(a: &Point<C>, b: &Point<C>) -> f64 {
+        let (dx, dy) = point_delta(a, b);
+        dx.hypot(dy)
+    }
+
+    fn min_distance_to_boundary(point: &Point<C>, boundary: &Boundary<C>) -> f64 {
+        let (dx, dy) = boundary_delta(point, boundary);
+        dx.hypot(dy)
+    }
+}
+
+impl<C> Metric<C> for Manhattan
+where
+    C: Coordinate,
+{
+    fn distance(a: &Point<C>, b: &Point<C>) -> f64 {
+        let (dx, dy) = point_delta(a, b);
+        dx + dy
+    }
+
+    fn min_distance_to_boundary(point: &Point<C>, boundary: &Boundary<C>) -> f64 {
+        let (dx, dy) = boundary_delta(point, boundary);
+        dx + dy
+    }
+}
+
+impl<C> Metric<C> for Chebyshev
+where
+    C: Coordinate,
+{
+    fn distance(a: &Point<C>, b: &Point<C>) -> f64 {
+        let (dx, dy) = point_delta(a, b);
+        dx.max(dy)
+    }
+
+    fn min_distance_to_boundary(point: &Point<C>, boundary: &Boundary<C>) -> f64 {
+        let (dx, dy) = boundary_delta(point, boundary);
+        dx.max(dy)
+    }
+}
+
+/// Absolute distance along each axis.
+/// The values are converted to f64 first to avoid underflows with unsigned coordinates.
+fn point_delta<C: Coordinate>(a: &Point<C>, b: &Point<C>) -> (f64, f64) {
+    (
+        (to_f64(a.x) - to_f64(b.x)).abs(),
+        (to_f64(a.y) - to_f64(b.y)).abs(),
+    )
+}
+
+/// Distance along each axis to the closest edge of the boundary or 0 if the point is between the edges.
+fn boundary_delta<C: Coordinate>(point: &Point<C>, boundary: &Boundary<C>) -> (f64, f64) {
+    let axis = |p: C, min: C, max: C| {
+        let (p, min, max) = (to_f64(p), to_f64(min), to_f64(max));
+        (min - p).max(p - max).max(0.)
+    };
+    (
+        axis(point.x, boundary.p1.x, boundary.p2.x),
+        axis(point.y, boundary.p1.y, boundary.p2.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Chebyshev, Euclidean, Manhattan, Metric};
+    use crate::{Boundary, Point};
+    use test_case::test_case;
+
+    #[test_case((0, 0), (3, 4) => 5.; "euclidean")]
+    #[test_case((3, 4), (0, 0) => 5.; "euclidean swapped")]
+    fn euclidean_distance(a: (u32, u32), b: (u32, u32)) -> f64 {
+        Euclidean::distance(&a.into(), &b.into())
+    }
+
+    #[test_case((0, 0), (3, 4) => 7.; "manhattan")]
+    #[test_case((3, 4), (0, 0) => 7.; "manhattan swapped")]
+    fn manhattan_distance(a: (u32, u32), b: (u32, u32)) -> f64 {
+        Manhattan::distance(&a.into(), &b.into())
+    }
+
+    #[test_case((0, 0), (3, 4) => 4.; "chebyshev")]
+    #[test_case((3, 4), (0, 0) => 4.; "chebyshev swapped")]
+    fn chebyshev_distance(a: (u32, u32), b: (u32, u32)) -> f64 {
+        Chebyshev::distance(&a.into(), &b.into())
+    }
+
+    #[test_case(5, 5 => 0.; "inside")]
+    #[test_case(0, 5 => 2.; "left")]
+    #[test_case(15, 5 => 5.; "right")]
+    #[test_case(5, 0 => 2.; "above")]
+    #[test_case(5, 13 => 3.; "below")]
+    #[test_case(0, 0 => 8f64.sqrt(); "corner")]
+    fn euclidean_min_distance_to_boundary(x: u32, y: u32) -> f64 {
+        let b = Boundary::between_points((2, 2), (10, 10));
+        Euclidean::min_distance_to_boundary(&Point::new(x, y), &b)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{boundary::to_f64, Boundary, Coordinate, Point};
+
+/// Number of subdivisions per axis used to compute the index
+const ORDER: u32 = 16;
+
+/// Position of `point` along the Morton curve (Z-order) covering `boundary`.
+///
+/// Like the quadrants of a node the curve visits top left, top right, bottom left and bottom right.
+/// The point is normalized to a grid of 2^16 × 2^16 cells inside the boundary.
+/// Points outside of the boundary are clamped to its border.
+pub(crate) fn index<C>(boundary: &Boundary<C>, point: &Point<C>) -> u64
+where
+    C: Coordinate,
+{
+    let max = (1u32 << ORDER) - 1;
+    let normalize = |c: C, p1: C, p2: C| {
+        let extent = to_f64(p2) - to_f64(p1);
+        if extent > 0. {
+            ((to_f64(c) - to_f64(p1)) / extent * f64::from(max)).clamp(0., f64::from(max)) as u32
+        } else {
+            0
+        }
+    };
+    let x = normalize(point.x, boundary.p1.x, boundary.p2.x);
+    let y = normalize(point.y, boundary.p1.y, boundary.p2.y);
+    (0..ORDER).rev().fold(0, |index, bit| {
+        let rx = u64::from((x >> bit) & 1);
+        let ry = u64::from((y >> bit) & 1);
+        index << 2 | ry << 1 | rx
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Boundary, Point};
+
+    #[test]
+    fn follows_curve_on_small_grid() {
+        // Morton curve of order 2
+        let curve = [
+            (0, 0),
+            (1, 0),
+            (0, 1),
+            (1, 1),
+            (2, 0),
+            (3, 0),
+            (2, 1),
+            (3, 1),
+            (0, 2),
+            (1, 2),
+            (0, 3),
+            (1, 3),
+            (2, 2),
+            (3, 2),
+            (2, 3),
+            (3, 3),
+        ];
+        let boundary = Boundary::new((0, 0), 3, 3);
+        let indices = curve
+            .iter()
+            .map(|p| super::index(&boundary, &Point::from(*p)))
+            .collect::<Vec<_>>();
+        assert!(indices.windows(2).all(|w| w[0] < w[1]), "{indices:?}");
+        assert_eq!(indices[0], 0);
+        assert_eq!(indices[15], (1 << 32) - 1);
+    }
+}
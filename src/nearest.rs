@@ -0,0 +1,162 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+use crate::{bounds::Capacity, Coordinate, Metric, Point, QuadTree};
+
+/// Heap entry ordered by its distance only.
+struct ByDistance<T> {
+    distance: f64,
+    value: T,
+}
+
+impl<T> PartialEq for ByDistance<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for ByDistance<T> {}
+
+impl<T> PartialOrd for ByDistance<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for ByDistance<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance)
+    }
+}
+
+/// Best first search for the `k` items closest to `point` which are at most `max_distance` away.
+/// Results are sorted by distance, nearest first.
+pub(crate) fn k_nearest<'a, M, C, Item, Cap>(
+    tree: &'a QuadTree<C, Item, Cap>,
+    point: &Point<C>,
+    k: usize,
+    max_distance: f64,
+) -> Vec<(f64, &'a (Point<C>, Item))>
+where
+    M: Metric<C>,
+    C: Coordinate,
+    Cap: Capacity,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+    // Max heap, the worst of the current best k items is on top
+    let mut best: BinaryHeap<ByDistance<&'a (Point<C>, Item)>> =
+        BinaryHeap::with_capacity(k.min(tree.len()).saturating_add(1));
+    // Min heap of nodes to visit ordered by their smallest possible distance
+    let mut nodes = BinaryHeap::new();
+    nodes.push(Reverse(ByDistance {
+        distance: M::min_distance_to_boundary(point, &tree.boundary),
+        value: tree,
+    }));
+
+    while let Some(Reverse(ByDistance {
+        distance: node_distance,
+        value: node,
+    })) = nodes.pop()
+    {
+        if node_distance > max_distance
+            || (best.len() == k && best.peek().is_some_and(|b| node_distance >= b.distance))
+        {
+            break;
+        }
+        for item in node.items.iter().flatten() {
+            let distance = M::distance(point, &item.0);
+            if distance > max_distance {
+                continue;
+            }
+            if best.len() < k {
+                best.push(ByDistance {
+                    distance,
+                    value: item,
+                });
+            } else if best.peek().is_some_and(|b| distance < b.distance) {
+                best.pop();
+                best.push(ByDistance {
+                    distance,
+                    value: item,
+                });
+            }
+        }
+        for quad in node.quadrants.iter().flat_map(|q| q.iter()) {
+            nodes.push(Reverse(ByDistance {
+                distance: M::min_distance_to_boundary(point, &quad.boundary),
+                value: quad,
+            }));
+        }
+    }
+
+    best.into_sorted_vec()
+        .into_iter()
+        .map(|b| (b.distance, b.value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Boundary, Chebyshev, Euclidean, Manhattan, Metric, Point, QuadTree};
+    use rand::{Rng, SeedableRng};
+
+    fn nearest_matches_brute_force<M: Metric<i32>>() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 1000, 1000), 4);
+        let mut points = Vec::new();
+        for i in 0..500 {
+            let p = Point::new(rng.gen_range(0..=1000), rng.gen_range(0..=1000));
+            assert!(tree.insert_at(p, i).is_ok());
+            points.push(p);
+        }
+        for _ in 0..100 {
+            let query = Point::new(rng.gen_range(-100..=1100), rng.gen_range(-100..=1100));
+            let expected = points
+                .iter()
+                .map(|p| M::distance(&query, p))
+                .min_by(f64::total_cmp)
+                .unwrap();
+            let found = tree.nearest_with::<M>(query).unwrap();
+            assert_eq!(M::distance(&query, &points[*found]), expected);
+        }
+    }
+
+    #[test]
+    fn k_nearest_within_radius() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        for (i, p) in [(50, 50), (53, 54), (40, 50), (50, 65), (90, 90), (0, 0)]
+            .into_iter()
+            .enumerate()
+        {
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        // Only three items are in range, so fewer than k are returned
+        assert_eq!(tree.k_nearest_within((50, 50), 5, 10), vec![&0, &1, &2]);
+        assert_eq!(tree.k_nearest_within((50, 50), 2, 10), vec![&0, &1]);
+        assert_eq!(tree.k_nearest_within((50, 50), 0, 10), Vec::<&usize>::new());
+        assert_eq!(
+            tree.k_nearest_within((50, 50), usize::MAX, 10),
+            vec![&0, &1, &2]
+        );
+        assert!(tree.k_nearest_within((20, 20), 5, 10).is_empty());
+    }
+
+    #[test]
+    fn nearest_euclidean() {
+        nearest_matches_brute_force::<Euclidean>();
+    }
+
+    #[test]
+    fn nearest_manhattan() {
+        nearest_matches_brute_force::<Manhattan>();
+    }
+
+    #[test]
+    fn nearest_chebyshev() {
+        nearest_matches_brute_force::<Chebyshev>();
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{boundary::to_f64, Area, Boundary, Coordinate, IntoPoint, Point};
+
+/// A rotated rectangle
+///
+/// The rotation is applied in `f64`, so coordinates are converted to `f64` for all checks.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OrientedBox<C>
+where
+    C: Coordinate,
+{
+    /// The center of the box
+    pub center: Point<C>,
+    /// Half of the width and height of the box before it is rotated
+    pub half_extents: (C, C),
+    /// The rotation around the center in radians. Positive angles rotate the x axis towards the y axis.
+    pub angle: f64,
+}
+
+impl<C> OrientedBox<C>
+where
+    C: Coordinate,
+{
+    /// Create a new box around `center`
+    /// # Example
+    /// ```
+    /// use qutee::*;
+    /// let b = OrientedBox::new((10., 10.), (4., 1.), std::f64::consts::FRAC_PI_4);
+    /// assert!(b.contains(&Point::new(12., 12.)));
+    /// assert!(!b.contains(&Point::new(12., 10.)));
+    /// ```
+    pub fn new(center: impl IntoPoint<C>, half_extents: (C, C), angle: f64) -> Self {
+        Self {
+            center: center.into_point(),
+            half_extents,
+            angle,
+        }
+    }
+
+    /// Unit vectors of the local x and y axis
+    fn axes(&self) -> [(f64, f64); 2] {
+        let (sin, cos) = self.angle.sin_cos();
+        [(cos, sin), (-sin, cos)]
+    }
+
+    fn corners(&self) -> [(f64, f64); 4] {
+        let [u, v] = self.axes();
+        let (cx, cy) = (to_f64(self.center.x), to_f64(self.center.y));
+        let (hx, hy) = (to_f64(self.half_extents.0), to_f64(self.half_extents.1));
+        [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)].map(|(sx, sy)| {
+            (
+                cx + sx * hx * u.0 + sy * hy * v.0,
+                cy + sx * hx * u.1 + sy * hy * v.1,
+            )
+        })
+    }
+
+    fn contains_f64(&self, (x, y): (f64, f64)) -> bool {
+        let [u, v] = self.axes();
+        let (dx, dy) = (x - to_f64(self.center.x), y - to_f64(self.center.y));
+        (dx * u.0 + dy * u.1).abs() <= to_f64(self.half_extents.0)
+            && (dx * v.0 + dy * v.1).abs() <= to_f64(self.half_extents.1)
+    }
+}
+
+fn boundary_corners<C: Coordinate>(boundary: &Boundary<C>) -> [(f64, f64); 4] {
+    let (x1, y1) = (to_f64(boundary.p1.x), to_f64(boundary.p1.y));
+    let (x2, y2) = (to_f64(boundary.p2.x), to_f64(boundary.p2.y));
+    [(x1, y1), (x2, y1), (x2, y2), (x1, y2)]
+}
+
+fn project(corners: &[(f64, f64); 4], (ax, ay): (f64, f64)) -> (f64, f64) {
+    corners
+        .iter()
+        .map(|(x, y)| x * ax + y * ay)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+            (min.min(p), max.max(p))
+        })
+}
+
+impl<C> Area<C> for OrientedBox<C>
+where
+    C: Coordinate,
+{
+    fn contains(&self, point: &Point<C>) -> bool {
+        self.contains_f64((to_f64(point.x), to_f64(point.y)))
+    }
+
+    /// Separating axis test using the axes of both rectangles
+    fn intersects(&self, boundary: &Boundary<C>) -> bool {
+        let own = self.corners();
+        let other = boundary_corners(boundary);
+        let [u, v] = self.axes();
+        [(1., 0.), (0., 1.), u, v].into_iter().all(|axis| {
+            let (min1, max1) = project(&own, axis);
+            let (min2, max2) = project(&other, axis);
+            max1 >= min2 && max2 >= min1
+        })
+    }
+
+    fn encloses(&self, boundary: &Boundary<C>) -> bool {
+        boundary_corners(boundary)
+            .into_iter()
+            .all(|corner| self.contains_f64(corner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
+
+    use test_case::test_case;
+
+    use crate::{Area, Boundary, OrientedBox, Point, QuadTree};
+
+    fn rotated() -> OrientedBox<i32> {
+        OrientedBox::new((10, 10), (4, 1), FRAC_PI_4)
+    }
+
+    #[test_case((10, 10) => true; "center")]
+    #[test_case((12, 12) => true; "along long axis")]
+    #[test_case((10, 11) => true; "near short axis")]
+    #[test_case((13, 13) => false; "just beyond long axis")]
+    #[test_case((11, 9) => false; "just beyond short axis")]
+    #[test_case((14, 10) => false; "inside unrotated box")]
+    fn contains(point: (i32, i32)) -> bool {
+        rotated().contains(&point.into())
+    }
+
+    #[test_case(Boundary::between_points((8, 8), (9, 9)) => true; "inside")]
+    #[test_case(Boundary::between_points((0, 0), (20, 20)) => true; "around")]
+    #[test_case(Boundary::between_points((13, 13), (20, 20)) => false; "separated by box axis")]
+    #[test_case(Boundary::between_points((14, 0), (20, 20)) => false; "separated by x axis")]
+    fn intersects(boundary: Boundary<i32>) -> bool {
+        rotated().intersects(&boundary)
+    }
+
+    #[test]
+    fn encloses() {
+        assert!(rotated().encloses(&Boundary::between_points((10, 10), (11, 11))));
+        assert!(!rotated().encloses(&Boundary::between_points((10, 10), (12, 11))));
+    }
+
+    #[test]
+    fn query() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 2);
+        for x in 0..=20 {
+            for y in 0..=20 {
+                assert!(tree.insert_at((x, y), Point::new(x, y)).is_ok());
+            }
+        }
+        let area = rotated();
+        let mut res = tree.query(area).copied().collect::<Vec<_>>();
+        let mut expected = tree
+            .iter()
+            .filter(|p| area.contains(p))
+            .copied()
+            .collect::<Vec<_>>();
+        res.sort();
+        expected.sort();
+        assert_eq!(res, expected);
+        assert!(res.contains(&Point::new(12, 12)));
+        assert!(!res.contains(&Point::new(13, 13)));
+    }
+}
@@ -0,0 +1,312 @@
+use std::io::{self, Read, Write};
+
+use crate::{
+    boundary::to_f64, bounds::ItemCapacity, Boundary, Coordinate, DynCap, Point, QuadTree,
+};
+
+/// Largest capacity accepted by `write_to` and `read_from`.
+/// The capacity is used to preallocate nodes, so a corrupt value must not be trusted.
+pub(crate) const MAX_CAPACITY: usize = 1 << 20;
+
+const ADAPTIVE_SPLIT: u8 = 1;
+const PREALLOCATE_ITEMS: u8 = 1 << 1;
+
+pub(crate) fn write_to<C, Item, Cap, W>(
+    tree: &QuadTree<C, Item, Cap>,
+    w: &mut W,
+    serialize: &mut impl FnMut(&Item) -> Vec<u8>,
+) -> io::Result<()>
+where
+    C: Coordinate,
+    Cap: ItemCapacity<Item>,
+    W: Write,
+{
+    let encoding = CoordinateEncoding::of::<C>();
+    w.write_all(&[encoding as u8])?;
+    let boundary = tree.boundary();
+    write_point(w, encoding, boundary.top_left())?;
+    write_point(w, encoding, boundary.bottom_right())?;
+    let capacity = tree.capacity();
+    if capacity > MAX_CAPACITY {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("capacity {capacity} is larger than {MAX_CAPACITY}"),
+        ));
+    }
+    write_u64(w, capacity as u64)?;
+    write_u64(w, tree.max_depth as u64)?;
+    let mut flags = 0;
+    if tree.adaptive_split {
+        flags |= ADAPTIVE_SPLIT;
+    }
+    if tree.preallocate_items {
+        flags |= PREALLOCATE_ITEMS;
+    }
+    w.write_all(&[flags])?;
+    write_u64(w, tree.len() as u64)?;
+    for (point, item) in tree.iter_points() {
+        write_point(w, encoding, point)?;
+        let bytes = serialize(item);
+        write_u64(w, bytes.len() as u64)?;
+        w.write_all(&bytes)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_from<C, Item, R>(
+    r: &mut R,
+    deserialize: &mut impl FnMut(&[u8]) -> io::Result<Item>,
+) -> io::Result<QuadTree<C, Item, DynCap>>
+where
+    C: Coordinate,
+    R: Read,
+{
+    let mut tag = [0];
+    r.read_exact(&mut tag)?;
+    let encoding = CoordinateEncoding::from_tag(tag[0])?;
+    let p1 = read_point(r, encoding)?;
+    let p2 = read_point(r, encoding)?;
+    let boundary = Boundary::try_between_points(p1, p2).map_err(|e| invalid_data(e.to_string()))?;
+    let capacity = read_len(r)?;
+    if capacity == 0 || capacity > MAX_CAPACITY {
+        return Err(invalid_data(format!("invalid capacity {capacity}")));
+    }
+    // A depth limit beyond `usize` can never be reached
+    let max_depth = usize::try_from(read_u64(r)?).unwrap_or(usize::MAX);
+    let mut flags = [0];
+    r.read_exact(&mut flags)?;
+    let [flags] = flags;
+    if flags & !(ADAPTIVE_SPLIT | PREALLOCATE_ITEMS) != 0 {
+        return Err(invalid_data(format!("unknown flags {flags:#b}")));
+    }
+    let mut tree = QuadTree::new_with_dyn_cap(boundary, capacity)
+        .with_max_depth(max_depth)
+        .with_adaptive_split(flags & ADAPTIVE_SPLIT != 0)
+        .with_preallocated_items(flags & PREALLOCATE_ITEMS != 0);
+    let mut buf = Vec::new();
+    for _ in 0..read_u64(r)? {
+        let point = read_point(r, encoding)?;
+        // The length is not trusted, so the buffer only grows with the bytes actually read
+        let len = read_u64(r)?;
+        buf.clear();
+        if r.by_ref().take(len).read_to_end(&mut buf)? as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let item = deserialize(&buf)?;
+        tree.insert_at(point, item)
+            .map_err(|e| invalid_data(e.to_string()))?;
+    }
+    Ok(tree)
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+fn write_u64(w: &mut impl Write, value: u64) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_point<C: Coordinate>(
+    w: &mut impl Write,
+    encoding: CoordinateEncoding,
+    point: &Point<C>,
+) -> io::Result<()> {
+    for c in [point.x, point.y] {
+        match encoding {
+            CoordinateEncoding::Float => w.write_all(&to_f64(c).to_le_bytes())?,
+            CoordinateEncoding::Integer => {
+                let value = c.to_i128().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{c:?} can not be stored as an integer"),
+                    )
+                })?;
+                w.write_all(&value.to_le_bytes())?
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_len(r: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(read_u64(r)?).map_err(invalid_data)
+}
+
+fn read_point<C: Coordinate>(
+    r: &mut impl Read,
+    encoding: CoordinateEncoding,
+) -> io::Result<Point<C>> {
+    let mut coordinate = || match encoding {
+        CoordinateEncoding::Float => {
+            let value = f64::from_bits(read_u64(r)?);
+            C::from(value).ok_or_else(|| invalid_data(format!("{value} is not a valid coordinate")))
+        }
+        CoordinateEncoding::Integer => {
+            let mut buf = [0; 16];
+            r.read_exact(&mut buf)?;
+            let value = i128::from_le_bytes(buf);
+            C::from(value).ok_or_else(|| invalid_data(format!("{value} is not a valid coordinate")))
+        }
+    };
+    Ok(Point::new(coordinate()?, coordinate()?))
+}
+
+/// How coordinates are stored. Integers are stored as `i128` so they do not lose precision.
+#[derive(Clone, Copy)]
+enum CoordinateEncoding {
+    Float = 0,
+    Integer = 1,
+}
+
+impl CoordinateEncoding {
+    fn of<C: Coordinate>() -> Self {
+        // Integer types truncate when converting from `f64`
+        if C::from(0.5).and_then(|half| half.to_f64()) == Some(0.5) {
+            Self::Float
+        } else {
+            Self::Integer
+        }
+    }
+
+    fn from_tag(tag: u8) -> io::Result<Self> {
+        match tag {
+            0 => Ok(Self::Float),
+            1 => Ok(Self::Integer),
+            _ => Err(invalid_data(format!("unknown coordinate encoding {tag}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor};
+
+    use rand::{Rng, SeedableRng};
+
+    use crate::{Boundary, Point, QuadTree};
+
+    #[test]
+    fn round_trip() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((-50, -50), 100, 100), 3);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(8);
+        for i in 0..200 {
+            let p = Point::new(rng.gen_range(-50..=50), rng.gen_range(-50..=50));
+            assert!(tree.insert_at(p, format!("item {i}")).is_ok());
+        }
+        let mut cursor = Cursor::new(Vec::new());
+        tree.write_to(&mut cursor, |item| item.as_bytes().to_vec())
+            .unwrap();
+        cursor.set_position(0);
+        let read = QuadTree::<i32, String>::read_from(&mut cursor, |bytes| {
+            String::from_utf8(bytes.to_vec())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .unwrap();
+        assert_eq!(read.boundary(), tree.boundary());
+        assert_eq!(read.capacity(), tree.capacity());
+        assert_eq!(read.max_depth, usize::MAX);
+        assert!(!read.adaptive_split);
+        assert!(!read.preallocate_items);
+        let mut expected = tree.iter_points().cloned().collect::<Vec<_>>();
+        let mut actual = read.iter_points().cloned().collect::<Vec<_>>();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn large_integer_coordinates() {
+        let max = u64::MAX;
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::between_points((0, 0), (max, max)), 1);
+        let points = [(1 << 53) + 1, max - 1, max].map(|c| Point::new(c, c));
+        for (i, point) in points.iter().enumerate() {
+            assert!(tree.insert_at(*point, i as u8).is_ok());
+        }
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes, |item| vec![*item]).unwrap();
+        let read =
+            QuadTree::<u64, u8>::read_from(&mut bytes.as_slice(), |bytes| Ok(bytes[0])).unwrap();
+        assert_eq!(read.boundary(), tree.boundary());
+        let mut actual = read.iter_points().cloned().collect::<Vec<_>>();
+        actual.sort();
+        assert_eq!(actual, points.into_iter().zip(0..).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn truncated_input() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 10., 10.), 3);
+        assert!(tree.insert_at((1., 2.), 7u8).is_ok());
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes, |item| vec![*item]).unwrap();
+        bytes.pop();
+        let err = QuadTree::<f64, u8>::read_from(&mut bytes.as_slice(), |bytes| Ok(bytes[0]))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn corrupt_item_length() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 10., 10.), 3);
+        assert!(tree.insert_at((1., 2.), 7u8).is_ok());
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes, |item| vec![*item]).unwrap();
+        // The item length directly precedes the single item byte
+        let len = bytes.len() - 9;
+        assert_eq!(bytes[0], 0);
+        bytes[len..len + 8].copy_from_slice(&(i64::MAX as u64).to_le_bytes());
+        let err = QuadTree::<f64, u8>::read_from(&mut bytes.as_slice(), |bytes| Ok(bytes[0]))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let tree = QuadTree::<_, u8>::new_with_dyn_cap(Boundary::new((0, 0), 64, 64), 2)
+            .with_max_depth(3)
+            .with_adaptive_split(true)
+            .with_preallocated_items(true);
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes, |item| vec![*item]).unwrap();
+        let read =
+            QuadTree::<i32, u8>::read_from(&mut bytes.as_slice(), |bytes| Ok(bytes[0])).unwrap();
+        assert_eq!(read.max_depth, 3);
+        assert!(read.adaptive_split);
+        assert!(read.preallocate_items);
+    }
+
+    #[test]
+    fn corrupt_capacity() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0., 0.), 10., 10.), 3);
+        assert!(tree.insert_at((1., 2.), 7u8).is_ok());
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes, |item| vec![*item]).unwrap();
+        // The capacity follows the tag byte and the two corners of the boundary
+        let start = 1 + 4 * 8;
+        assert_eq!(bytes[start..start + 8], 3u64.to_le_bytes());
+        for capacity in [0, u64::MAX / 2] {
+            bytes[start..start + 8].copy_from_slice(&capacity.to_le_bytes());
+            let err = QuadTree::<f64, u8>::read_from(&mut bytes.as_slice(), |bytes| Ok(bytes[0]))
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn capacity_too_large() {
+        let tree = QuadTree::<_, u8>::new_with_dyn_cap(
+            Boundary::new((0., 0.), 10., 10.),
+            super::MAX_CAPACITY + 1,
+        );
+        let err = tree
+            .write_to(&mut Vec::new(), |item| vec![*item])
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
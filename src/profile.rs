@@ -0,0 +1,71 @@
+use crate::{boundary::area_encloses, bounds::Capacity, Area, Coordinate, QuadTree};
+
+/// Statistics collected by `QuadTree::query_profiled`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct QueryProfile {
+    /// Number of nodes the query descended into
+    pub nodes_visited: usize,
+    /// Number of items whose point was checked against the area.
+    /// Items inside a node fully enclosed by the area are returned without a check.
+    pub items_tested: usize,
+    /// Number of items returned
+    pub items_returned: usize,
+}
+
+pub(crate) fn query_profiled<'a, C, A, Item, Cap>(
+    tree: &'a QuadTree<C, Item, Cap>,
+    area: &A,
+) -> (Vec<&'a Item>, QueryProfile)
+where
+    C: Coordinate,
+    A: Area<C>,
+    Cap: Capacity,
+{
+    let mut profile = QueryProfile::default();
+    let mut result = Vec::new();
+    let mut stack = vec![(tree, area_encloses(area, &tree.boundary))];
+    while let Some((node, is_enclosed_by_area)) = stack.pop() {
+        profile.nodes_visited += 1;
+        if let Some(items) = &node.items {
+            for (point, item) in items {
+                if !is_enclosed_by_area {
+                    profile.items_tested += 1;
+                    if !area.contains(point) {
+                        continue;
+                    }
+                }
+                result.push(item);
+            }
+        }
+        if let Some(quads) = &node.quadrants {
+            for quad in quads.iter().rev() {
+                if is_enclosed_by_area || area.intersects(&quad.boundary) {
+                    stack.push((
+                        quad,
+                        is_enclosed_by_area || area_encloses(area, &quad.boundary),
+                    ));
+                }
+            }
+        }
+    }
+    profile.items_returned = result.len();
+    (result, profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Boundary, QuadTree};
+
+    #[test]
+    fn returned_count_matches_result() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 20, 20), 2);
+        for i in 0..20 {
+            assert!(tree.insert_at((i, 19 - i), i).is_ok());
+        }
+        let (res, profile) = tree.query_profiled(Boundary::new((2, 2), 10, 10));
+        assert_eq!(profile.items_returned, res.len());
+        assert_eq!(res.len(), tree.query(Boundary::new((2, 2), 10, 10)).count());
+        assert!(profile.nodes_visited > 0);
+        assert!(profile.items_tested >= profile.items_returned);
+    }
+}
@@ -0,0 +1,165 @@
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{bounds::ItemCapacity, Boundary, Coordinate, DynCap, GrowCap, Point, QuadTree};
+
+/// The serialized form of a tree.
+/// Only the settings of the root and the items are stored, the nodes are rebuilt by inserting the items.
+#[derive(Serialize)]
+#[serde(
+    rename = "QuadTree",
+    bound = "C: Serialize, Item: Serialize, Cap: ItemCapacity<Item> + Serialize"
+)]
+struct TreeRef<'a, C, Item, Cap>
+where
+    C: Coordinate,
+{
+    boundary: &'a Boundary<C>,
+    capacity: Cap,
+    max_depth: Option<usize>,
+    adaptive_split: bool,
+    preallocate_items: bool,
+    items: Items<'a, C, Item, Cap>,
+}
+
+struct Items<'a, C, Item, Cap>(&'a QuadTree<C, Item, Cap>)
+where
+    C: Coordinate;
+
+impl<C, Item, Cap> Serialize for Items<'_, C, Item, Cap>
+where
+    C: Coordinate + Serialize,
+    Item: Serialize,
+    Cap: ItemCapacity<Item>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter_points())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "QuadTree")]
+struct TreeData<C, Item, Cap>
+where
+    C: Coordinate,
+{
+    boundary: Boundary<C>,
+    capacity: Cap,
+    #[serde(default)]
+    max_depth: Option<usize>,
+    #[serde(default)]
+    adaptive_split: bool,
+    #[serde(default)]
+    preallocate_items: bool,
+    items: Vec<(Point<C>, Item)>,
+}
+
+impl<C, Item, Cap> Serialize for QuadTree<C, Item, Cap>
+where
+    C: Coordinate + Serialize,
+    Item: Serialize,
+    Cap: ItemCapacity<Item> + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TreeRef {
+            boundary: &self.boundary,
+            capacity: self.capacity.for_new_node(),
+            max_depth: (self.max_depth != usize::MAX).then_some(self.max_depth),
+            adaptive_split: self.adaptive_split,
+            preallocate_items: self.preallocate_items,
+            items: Items(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, C, Item, Cap> Deserialize<'de> for QuadTree<C, Item, Cap>
+where
+    C: Coordinate + Deserialize<'de>,
+    Item: Deserialize<'de>,
+    Cap: ItemCapacity<Item> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = TreeData::<C, Item, Cap>::deserialize(deserializer)?;
+        let boundary = Boundary::try_between_points(data.boundary.p1, data.boundary.p2)
+            .map_err(D::Error::custom)?;
+        let mut tree = QuadTree::new_with_capacity(boundary, data.capacity)
+            .with_max_depth(data.max_depth.unwrap_or(usize::MAX))
+            .with_adaptive_split(data.adaptive_split)
+            .with_preallocated_items(data.preallocate_items);
+        for (point, item) in data.items {
+            tree.insert_at(point, item).map_err(D::Error::custom)?;
+        }
+        Ok(tree)
+    }
+}
+
+impl<'de> Deserialize<'de> for DynCap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        usize::deserialize(deserializer).map(DynCap::new)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "GrowCap")]
+struct GrowCapData {
+    initial: usize,
+    max: usize,
+}
+
+impl<'de> Deserialize<'de> for GrowCap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = GrowCapData::deserialize(deserializer)?;
+        Ok(GrowCap::new(data.initial, data.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bounds::Capacity, Boundary, DynCap, GrowCap, QuadTree};
+
+    #[test]
+    fn only_settings_and_items_are_stored() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        assert!(tree.insert_at((2, 2), 'a').is_ok());
+        assert!(tree.insert_at((8, 8), 'b').is_ok());
+        assert_eq!(
+            serde_json::to_string(&tree).unwrap(),
+            r#"{"boundary":{"p1":{"x":0,"y":0},"p2":{"x":10,"y":10}},"capacity":1,"max_depth":null,"adaptive_split":false,"preallocate_items":false,"items":[[{"x":2,"y":2},"a"],[{"x":8,"y":8},"b"]]}"#
+        );
+    }
+
+    #[test]
+    fn nodes_are_rebuilt() {
+        // Quadrants in the input are ignored instead of being trusted
+        let json = r#"{
+            "boundary": {"p1": {"x": 0, "y": 0}, "p2": {"x": 10, "y": 10}},
+            "capacity": 0,
+            "quadrants": [{"boundary": {"p1": {"x": 0, "y": 0}, "p2": {"x": 5, "y": 5}}}],
+            "items": [[{"x": 2, "y": 2}, 1], [{"x": 8, "y": 8}, 2]]
+        }"#;
+        let mut tree = serde_json::from_str::<QuadTree<i32, i32>>(json).unwrap();
+        assert_eq!(tree.capacity(), 1);
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.insert_at((9, 1), 3).is_ok());
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn invalid_items_are_rejected() {
+        let json = r#"{
+            "boundary": {"p1": {"x": 0, "y": 0}, "p2": {"x": 10, "y": 10}},
+            "capacity": 2,
+            "items": [[{"x": 20, "y": 2}, 1]]
+        }"#;
+        assert!(serde_json::from_str::<QuadTree<i32, i32>>(json).is_err());
+    }
+
+    #[test]
+    fn capacities_are_normalized() {
+        assert_eq!(serde_json::from_str::<DynCap>("0").unwrap(), DynCap::new(1));
+        let cap = serde_json::from_str::<GrowCap>(r#"{"initial":0,"current":0,"max":0}"#).unwrap();
+        assert_eq!(cap, GrowCap::new(1, 1));
+        assert_eq!(cap.capacity(), 1);
+    }
+}
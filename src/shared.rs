@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use crate::{
+    bounds::ItemCapacity, Area, Boundary, Coordinate, DynCap, IntoPoint, Iter, IterPoints, Metric,
+    QuadTree, Query, QueryPoints,
+};
+
+/// A read only quad tree which can be cheaply cloned and shared between threads.
+/// Created by `QuadTree::share`.
+#[derive(Debug)]
+pub struct SharedQuadTree<C, Item, Cap = DynCap>(Arc<QuadTree<C, Item, Cap>>)
+where
+    C: Coordinate;
+
+impl<C, Item, Cap> SharedQuadTree<C, Item, Cap>
+where
+    Cap: ItemCapacity<Item>,
+    C: Coordinate,
+{
+    pub(super) fn new(tree: QuadTree<C, Item, Cap>) -> Self {
+        Self(Arc::new(tree))
+    }
+
+    /// Get all items in a given area.
+    pub fn query<A>(&self, area: A) -> Query<'_, C, A, Item, Cap>
+    where
+        A: Area<C>,
+    {
+        self.0.query(area)
+    }
+
+    /// Get all items in a given area and their coordinates.
+    pub fn query_points<A>(&self, area: A) -> QueryPoints<'_, C, A, Item, Cap>
+    where
+        A: Area<C>,
+    {
+        self.0.query_points(area)
+    }
+
+    /// Get the item closest to a point using a given distance metric.
+    pub fn nearest_with<M>(&self, point: impl IntoPoint<C>) -> Option<&Item>
+    where
+        M: Metric<C>,
+    {
+        self.0.nearest_with::<M>(point)
+    }
+
+    /// Get an iterator over all items.
+    pub fn iter(&self) -> Iter<'_, C, Item, Cap> {
+        self.0.iter()
+    }
+
+    /// Get an iterator over all items and their coordinates.
+    pub fn iter_points(&self) -> IterPoints<'_, C, Item, Cap> {
+        self.0.iter_points()
+    }
+
+    /// Returns the boundary of the tree
+    pub fn boundary(&self) -> &Boundary<C> {
+        self.0.boundary()
+    }
+
+    /// Returns the capacity
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Returns the tree if this is the only reference to it.
+    /// # Errors
+    /// Returns `self` if the tree is still shared.
+    pub fn try_unwrap(self) -> Result<QuadTree<C, Item, Cap>, Self> {
+        Arc::try_unwrap(self.0).map_err(Self)
+    }
+}
+
+impl<C, Item, Cap> Clone for SharedQuadTree<C, Item, Cap>
+where
+    C: Coordinate,
+{
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Boundary, QuadTree};
+
+    #[test]
+    fn query_from_threads() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 4);
+        for i in 0..100 {
+            assert!(tree.insert_at((i, i), i).is_ok());
+        }
+        let shared = tree.share();
+        let handles = (0..4)
+            .map(|t| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    shared
+                        .query(Boundary::new((t * 25, t * 25), 24, 24))
+                        .count()
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 25);
+        }
+        assert_eq!(shared.try_unwrap().unwrap().iter().count(), 100);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+use crate::{bounds::ItemCapacity, Area, Boundary, Coordinate, Point, QuadTree};
+
+/// Broken invariants found by `QuadTree::validate`
+#[derive(PartialEq, Eq, Clone)]
+pub enum ValidationError<C>
+where
+    C: Coordinate,
+{
+    /// A node holds a point outside of its boundary
+    PointOutsideNode(Boundary<C>, Point<C>),
+    /// The quadrants of a node do not match the split of its boundary
+    QuadrantsDoNotTile(Boundary<C>),
+    /// A leaf which could be split holds more items than its capacity allows
+    OverCapacity(Boundary<C>),
+    /// A node has quadrants but none of them holds an item
+    EmptyQuadrants(Boundary<C>),
+}
+
+pub(crate) fn validate<C, Item, Cap>(
+    tree: &QuadTree<C, Item, Cap>,
+) -> Result<(), ValidationError<C>>
+where
+    C: Coordinate,
+    Cap: ItemCapacity<Item>,
+{
+    let items = tree.items.as_deref().unwrap_or_default();
+    if let Some((point, _)) = items.iter().find(|(p, _)| !tree.boundary.contains(p)) {
+        return Err(ValidationError::PointOutsideNode(tree.boundary, *point));
+    }
+    let Some(quads) = &tree.quadrants else {
+        let fits = tree.capacity.has_room_for_all(items);
+        if !fits && tree.split_boundaries().is_some() {
+            return Err(ValidationError::OverCapacity(tree.boundary));
+        }
+        return Ok(());
+    };
+    let expected = tree.split_boundaries().unwrap_or_default();
+    if !quads.iter().map(|q| q.boundary).eq(expected) {
+        return Err(ValidationError::QuadrantsDoNotTile(tree.boundary));
+    }
+    if quads.iter().all(|q| q.is_empty()) {
+        return Err(ValidationError::EmptyQuadrants(tree.boundary));
+    }
+    quads.iter().try_for_each(validate)
+}
+
+impl<C> Error for ValidationError<C> where C: Coordinate {}
+
+impl<C> Display for ValidationError<C>
+where
+    C: Coordinate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl<C> Debug for ValidationError<C>
+where
+    C: Coordinate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PointOutsideNode(boundary, point) => {
+                write!(f, "point {point} is stored outside of node {boundary}")
+            }
+            Self::QuadrantsDoNotTile(boundary) => {
+                write!(f, "quadrants of node {boundary} do not tile its area")
+            }
+            Self::OverCapacity(boundary) => {
+                write!(f, "leaf {boundary} holds more items than its capacity")
+            }
+            Self::EmptyQuadrants(boundary) => {
+                write!(f, "node {boundary} has only empty quadrants")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use crate::{Boundary, Point, QuadTree, ValidationError};
+
+    fn tree() -> QuadTree<i32, i32> {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 100, 100), 2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(722);
+        for i in 0..100 {
+            let p = Point::new(rng.gen_range(0..=100), rng.gen_range(0..=100));
+            assert!(tree.insert_at(p, i).is_ok());
+        }
+        tree
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(tree().validate(), Ok(()));
+        let mut tree = tree();
+        tree.clear_area(Boundary::new((0, 0), 60, 60));
+        assert_eq!(tree.validate(), Ok(()));
+        tree.drain_while(|_, _| false);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn point_outside_node() {
+        let mut tree = tree();
+        let quad = &mut tree.quadrants.as_mut().unwrap()[0];
+        let boundary = quad.boundary;
+        quad.items
+            .get_or_insert_with(Vec::new)
+            .push((Point::new(90, 90), -1));
+        assert_eq!(
+            tree.validate(),
+            Err(ValidationError::PointOutsideNode(
+                boundary,
+                Point::new(90, 90)
+            ))
+        );
+    }
+
+    #[test]
+    fn quadrants_do_not_tile() {
+        let mut tree = tree();
+        let quads = tree.quadrants.as_mut().unwrap();
+        quads.swap(1, 2);
+        assert_eq!(
+            tree.validate(),
+            Err(ValidationError::QuadrantsDoNotTile(tree.boundary))
+        );
+    }
+
+    #[test]
+    fn over_capacity() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 2);
+        tree.items = Some(vec![
+            (Point::new(1, 1), 1),
+            (Point::new(2, 2), 2),
+            (Point::new(3, 3), 3),
+        ]);
+        assert_eq!(
+            tree.validate(),
+            Err(ValidationError::OverCapacity(tree.boundary))
+        );
+        // Nodes which can not be split may exceed their capacity
+        tree.boundary = Boundary::new((0, 0), 0, 10);
+        tree.items = Some(vec![
+            (Point::new(0, 1), 1),
+            (Point::new(0, 2), 2),
+            (Point::new(0, 3), 3),
+        ]);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn empty_quadrants() {
+        let mut tree = QuadTree::new_with_dyn_cap(Boundary::new((0, 0), 10, 10), 1);
+        assert!(tree.insert_at((1, 1), 1).is_ok());
+        assert!(tree.insert_at((8, 8), 2).is_ok());
+        tree.quadrants.as_mut().unwrap()[3].items = None;
+        assert_eq!(
+            tree.validate(),
+            Err(ValidationError::EmptyQuadrants(tree.boundary))
+        );
+    }
+}